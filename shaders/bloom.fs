@@ -0,0 +1,37 @@
+#version 330
+
+in vec2 fragTexCoord;
+in vec4 fragColor;
+
+uniform sampler2D texture0;
+uniform vec4 colDiffuse;
+
+out vec4 finalColor;
+
+const int RADIUS = 3;
+
+uniform vec2 resolution = vec2(512.0, 512.0);
+uniform float intensity = 1.0;
+uniform float crt_intensity = 1.0;
+uniform float threshold = 0.6;
+uniform float strength = 0.8;
+uniform float spread = 2.0;
+
+void main() {
+    vec4 source = texture(texture0, fragTexCoord);
+    vec2 texel = spread / resolution;
+
+    // a gaussian weighted blur of only the parts brighter than the threshold
+    vec3 glow = vec3(0.0);
+    float total = 0.0;
+    for (int x = -RADIUS; x <= RADIUS; x++) {
+        for (int y = -RADIUS; y <= RADIUS; y++) {
+            float weight = exp(-float(x*x + y*y) / 8.0);
+            vec3 tap = texture(texture0, fragTexCoord + vec2(x, y)*texel).rgb;
+            glow += max(tap - threshold, 0.0) * weight;
+            total += weight;
+        }
+    }
+
+    finalColor = vec4(source.rgb + glow/total * strength * intensity * crt_intensity, source.a);
+}
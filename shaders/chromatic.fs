@@ -0,0 +1,25 @@
+#version 330
+
+in vec2 fragTexCoord;
+in vec4 fragColor;
+
+uniform sampler2D texture0;
+uniform vec4 colDiffuse;
+
+out vec4 finalColor;
+
+uniform vec2 resolution = vec2(512.0, 512.0);
+uniform float intensity = 1.0;
+uniform float crt_intensity = 1.0;
+uniform float offset = 1.5;
+
+void main() {
+    // red and blue drift apart towards the edges, green stays put
+    vec2 shift = (fragTexCoord - 0.5) * 2.0 * offset * intensity * crt_intensity / resolution;
+
+    vec4 color = texture(texture0, fragTexCoord);
+    color.r = texture(texture0, fragTexCoord + shift).r;
+    color.b = texture(texture0, fragTexCoord - shift).b;
+
+    finalColor = color;
+}
@@ -0,0 +1,32 @@
+#version 330
+
+in vec2 fragTexCoord;
+in vec4 fragColor;
+
+uniform sampler2D texture0;
+uniform vec4 colDiffuse;
+
+out vec4 finalColor;
+
+uniform float intensity = 1.0;
+uniform float crt_intensity = 1.0;
+uniform float curvature = 0.25;
+uniform float vignette = 0.3;
+
+void main() {
+    // bulge the picture outwards like the face of a tube
+    vec2 uv = fragTexCoord*2.0 - 1.0;
+    vec2 offset = uv.yx * curvature * intensity * crt_intensity;
+    uv = (uv + uv*offset*offset)*0.5 + 0.5;
+
+    if (uv.x < 0.0 || uv.x > 1.0 || uv.y < 0.0 || uv.y > 1.0) {
+        finalColor = vec4(0.0, 0.0, 0.0, 1.0);
+        return;
+    }
+
+    vec4 color = texture(texture0, uv);
+    float edge = uv.x * uv.y * (1.0 - uv.x)*(1.0 - uv.y) * 16.0;
+    color.rgb *= mix(1.0, pow(edge, 0.25), vignette * intensity * crt_intensity);
+
+    finalColor = color;
+}
@@ -0,0 +1,26 @@
+#version 330
+
+in vec2 fragTexCoord;
+in vec4 fragColor;
+
+uniform sampler2D texture0;
+uniform vec4 colDiffuse;
+
+out vec4 finalColor;
+
+// the accumulated picture from last frame, kept by the post chain
+uniform sampler2D history;
+
+// how much of last frame survives, worked out from the frame time each frame
+uniform float keep = 0.9;
+// in the post chain the crt slider fades the trails out with everything else
+uniform float crt_intensity = 1.0;
+
+void main() {
+    vec4 source = texture(texture0, fragTexCoord);
+    vec4 previous = texture(history, fragTexCoord);
+
+    // fade exponentially, the small floor makes sure faint trails actually reach black
+    vec3 faded = max(previous.rgb*keep*crt_intensity - 1.0/255.0, 0.0);
+    finalColor = vec4(max(source.rgb, faded), 1.0);
+}
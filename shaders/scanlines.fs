@@ -0,0 +1,33 @@
+#version 330
+
+in vec2 fragTexCoord;
+in vec4 fragColor;
+
+uniform sampler2D texture0;
+uniform vec4 colDiffuse;
+
+out vec4 finalColor;
+
+const vec4 OVERLAY = vec4(0.9, 1.0, 0.7, 1);
+
+uniform float time;
+// each pass's own strength from post.toml, and the crt slider over all of them
+uniform float intensity = 1.0;
+uniform float crt_intensity = 1.0;
+
+void main() {
+    vec2 uv = 0.5 + (fragTexCoord - 0.5)*0.7;
+
+    vec4 source = texture(texture0, fragTexCoord);
+    vec4 color = source;
+    vec4 prime = vec4(color * color * 1.2);
+
+    color = clamp(prime, 0.1, 1.0);
+    color *= 0.5 + 0.5 * 18.0 * uv.x * uv.y * (1.0 - uv.x)*(1.0 - uv.y);
+    color *= OVERLAY;
+    color *= 0.9 + 0.1*sin(5.0*time + uv.y*1000.0);
+    color *= 0.9;
+
+    // intensity fades between the untouched frame and the full crt look
+    finalColor = mix(source, color, intensity * crt_intensity);
+}
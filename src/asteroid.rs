@@ -0,0 +1,151 @@
+use raylib_ffi::*;
+use colors::*;
+use crate::rl::physics::{self, Body};
+use crate::rl::timer::Timer;
+use crate::rl::vector::vector2 as v;
+use crate::rules::Rules;
+use crate::ship::{Ship, ShipEvent};
+use crate::rl::view::{ARENA_WIDTH, ARENA_HEIGHT};
+use std::f32::consts::PI;
+use rand::Rng;
+
+/// Pieces smaller than this are destroyed outright instead of splitting off
+const MIN_RADIUS: f32 = 6.0;
+
+pub struct Asteroid {
+    pub center: Vector2,
+    velocity: Vector2,
+    angle: f32,
+    spin: f32,
+    pub radius: f32,
+    shape: Vec<f32>,
+}
+
+impl Asteroid {
+    pub fn new(center: Vector2, velocity: Vector2, radius: f32) -> Asteroid {
+        let mut rng = rand::thread_rng();
+        Asteroid {
+            center,
+            velocity,
+            angle: rng.gen_range(0.0..2.0 * PI),
+            spin: rng.gen_range(-1.5..1.5),
+            radius,
+            shape: (0..9).map(|_| rng.gen_range(0.7..1.0)).collect(),
+        }
+    }
+
+    /// @brief  A large asteroid on a rough orbit, kept clear of the given points
+    pub unsafe fn spawn(main_star: &Vector2, avoid: &[Vector2]) -> Asteroid {
+        let mut rng = rand::thread_rng();
+        let mut center = v::zero();
+        let mut radius = 0.0;
+        for _ in 0..16 {
+            radius = rng.gen_range(120.0..230.0);
+            center = v::add(main_star, &v::from_angle(rng.gen_range(0.0..2.0 * PI), radius));
+            if avoid.iter().all(|p| v::dist(p, &center) > 80.0) { break }
+        }
+        let angle = v::angle_from_line(main_star, &center);
+        let direction = if rng.gen_bool(0.5) { PI / 2.0 } else { -PI / 2.0 };
+        let speed = physics::orbit_speed(radius) * rng.gen_range(0.8..1.1);
+        Asteroid::new(center, v::from_angle(angle + direction, speed), 24.0)
+    }
+
+    /// @brief  The two halves left after a hit, empty if it was too small to survive
+    pub fn split(&self) -> Vec<Asteroid> {
+        if self.radius / 2.0 < MIN_RADIUS {
+            return vec![]
+        }
+        let kick = v::rotate(&v::norm(&self.velocity), PI / 2.0);
+        let kick = v::scale(&kick, 30.0);
+        vec![
+            Asteroid::new(self.center, v::add(&self.velocity, &kick), self.radius / 2.0),
+            Asteroid::new(self.center, v::sub(&self.velocity, &kick), self.radius / 2.0),
+        ]
+    }
+
+    pub unsafe fn update(&mut self, main_star: &Vector2, rules: &Rules, delta: f32) {
+        let mut body = Body { position: self.center, velocity: self.velocity };
+        physics::integrate(&mut body, main_star, &v::zero(), rules.integrator, delta);
+        self.velocity = body.velocity;
+        self.center = v::wrap(&body.position, ARENA_WIDTH as f32, ARENA_HEIGHT as f32);
+        self.angle += self.spin * delta;
+    }
+
+    pub fn hull(&self) -> Vec<Vector2> {
+        let step = 2.0 * PI / self.shape.len() as f32;
+        self.shape.iter().enumerate()
+            .map(|(i, r)| v::add(&self.center, &v::from_angle(self.angle + step * i as f32, self.radius * r)))
+            .collect()
+    }
+
+    pub unsafe fn hits_ship(&self, ship: &Ship) -> bool {
+        if v::dist(&self.center, &ship.center) < self.radius * 0.7 {
+            return true
+        }
+        let hull = self.hull();
+        (0..hull.len()).any(|i| ship.line_hits(hull[i], hull[(i + 1) % hull.len()]))
+    }
+
+    pub fn contains(&self, point: &Vector2) -> bool {
+        v::dist(&self.center, point) < self.radius
+    }
+
+    pub unsafe fn draw(&self) {
+        let hull = self.hull();
+        for i in 0..hull.len() {
+            DrawLineV(hull[i], hull[(i + 1) % hull.len()], LIGHTGRAY);
+        }
+    }
+}
+
+/// Keeps a number of asteroids in orbit, splitting them when shot
+pub struct AsteroidField {
+    pub asteroids: Vec<Asteroid>,
+    spawn_timer: Timer,
+}
+
+impl AsteroidField {
+    pub unsafe fn new(main_star: &Vector2, rules: &Rules, avoid: &[Vector2]) -> AsteroidField {
+        let count = if rules.asteroids { rules.asteroid_count } else { 0 };
+        AsteroidField {
+            asteroids: (0..count).map(|_| Asteroid::spawn(main_star, avoid)).collect(),
+            spawn_timer: Timer::new(6.0),
+        }
+    }
+
+    pub unsafe fn update(&mut self, main_star: &Vector2, rules: &Rules, avoid: &[Vector2], delta: f32) {
+        if !rules.asteroids { return }
+        self.spawn_timer.update(delta);
+        if !self.spawn_timer.is_running() && self.asteroids.len() < rules.asteroid_count {
+            self.asteroids.push(Asteroid::spawn(main_star, avoid));
+            self.spawn_timer.start();
+        }
+        self.asteroids.iter_mut().for_each(|a| a.update(main_star, rules, delta));
+        self.asteroids.retain(|a| !a.contains(main_star));
+    }
+
+    /// @brief  Removes the ship's rockets that hit an asteroid and splits what they hit
+    pub fn shoot(&mut self, ship: &mut Ship) {
+        let mut pieces: Vec<Asteroid> = vec![];
+        let events = &mut ship.events;
+        ship.rockets.retain(|r| {
+            match self.asteroids.iter().position(|a| a.contains(&r.center)) {
+                Some(i) => {
+                    pieces.extend(self.asteroids.remove(i).split());
+                    events.push(ShipEvent::RocketHit(r.center));
+                    false
+                },
+                None => true,
+            }
+        });
+        self.asteroids.extend(pieces);
+    }
+
+    pub unsafe fn hits(&self, ship: &Ship) -> bool {
+        !ship.dead && self.asteroids.iter().any(|a| a.hits_ship(ship))
+    }
+
+    pub unsafe fn draw(&self) {
+        self.asteroids.iter().for_each(|a| a.draw());
+    }
+}
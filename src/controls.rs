@@ -0,0 +1,180 @@
+use raylib_ffi::*;
+use colors::*;
+use crate::key;
+use crate::rl::gui::{draw_label, draw_text, TextPosition, Theme};
+use crate::rl::input::{button_name, button_pressed_on, key_name, menu_pressed, Action, InputMap, MenuKey};
+use crate::rl::view::ARENA_WIDTH;
+
+/// The rebinding screen, a column of actions for each player slot
+pub struct ControlsScreen {
+    row: usize,
+    slot: usize,
+    capturing: bool,
+    gamepad: bool,
+    theme: Theme,
+}
+
+impl ControlsScreen {
+    pub fn new(theme: Theme) -> ControlsScreen {
+        ControlsScreen { row: 0, slot: 0, capturing: false, gamepad: false, theme }
+    }
+
+    fn conflicts(&self, input: &InputMap, slot: usize, action: Action) -> Vec<(usize, Action)> {
+        if self.gamepad {
+            input.button_conflicts(slot, action)
+        } else {
+            input.conflicts(slot, action)
+        }
+    }
+
+    /// @brief  Rows the cursor moves over, the gamepad page adds the pad and deadzone
+    fn rows(&self) -> usize {
+        Action::ALL.len() + if self.gamepad { 2 } else { 0 }
+    }
+
+    /// @brief  Handles input, returns true when the player backs out to the menu
+    pub unsafe fn update(&mut self, input: &mut InputMap) -> bool {
+        if self.capturing {
+            let action = Action::ALL[self.row];
+            let key = GetKeyPressed();
+            // only the slot's own pad can bind, so someone else's held button can't sneak in
+            let button = input.slots[self.slot].pad().and_then(|pad| button_pressed_on(pad));
+            if key == key!(Escape) {
+                self.capturing = false;
+            } else if let Some(button) = button.filter(|_| self.gamepad) {
+                input.slots[self.slot].set_button(action, button);
+                self.save(input);
+                self.capturing = false;
+            } else if !self.gamepad && key != 0 {
+                input.slots[self.slot].set(action, key);
+                self.save(input);
+                self.capturing = false;
+            }
+            return false
+        }
+        if menu_pressed(MenuKey::Back) {
+            return true
+        }
+        let rows = self.rows();
+        if menu_pressed(MenuKey::Up) {
+            self.row = (self.row + rows - 1) % rows;
+        }
+        if menu_pressed(MenuKey::Down) {
+            self.row = (self.row + 1) % rows;
+        }
+        if menu_pressed(MenuKey::Switch) {
+            self.gamepad = !self.gamepad;
+            self.row = self.row.min(self.rows() - 1);
+        }
+        let deadzone_row = self.gamepad && self.row == Action::ALL.len() + 1;
+        if !deadzone_row && (menu_pressed(MenuKey::Left) || menu_pressed(MenuKey::Right)) {
+            self.slot = (self.slot + 1) % input.slots.len();
+        }
+        if let Some(&action) = Action::ALL.get(self.row) {
+            if menu_pressed(MenuKey::Accept) {
+                self.capturing = true;
+            }
+            if menu_pressed(MenuKey::Clear) {
+                match self.gamepad {
+                    true => input.slots[self.slot].set_button(action, 0),
+                    false => input.slots[self.slot].set(action, 0),
+                }
+                self.save(input);
+            }
+        }
+        if self.gamepad {
+            let pad_row = self.row == Action::ALL.len();
+            let bindings = &mut input.slots[self.slot];
+            let changed = if IsKeyPressed(key!(G)) || (pad_row && menu_pressed(MenuKey::Accept)) {
+                bindings.gamepad = if bindings.gamepad >= 3 { -1 } else { bindings.gamepad + 1 };
+                true
+            } else if IsKeyPressed(key!(Minus)) || (deadzone_row && menu_pressed(MenuKey::Left)) {
+                bindings.deadzone = (bindings.deadzone - 0.05).max(0.0);
+                true
+            } else if IsKeyPressed(key!(Equal)) || (deadzone_row && menu_pressed(MenuKey::Right)) {
+                bindings.deadzone = (bindings.deadzone + 0.05).min(0.9);
+                true
+            } else {
+                false
+            };
+            if changed {
+                self.save(input);
+            }
+        }
+        if IsKeyPressed(key!(F12)) {
+            input.reset();
+            self.save(input);
+        }
+        false
+    }
+
+    fn save(&self, input: &InputMap) {
+        if let Err(e) = input.save() {
+            eprintln!("could not save controls: {}", e);
+        }
+    }
+
+    pub unsafe fn draw(&self, input: &InputMap) {
+        let t = &self.theme;
+        let width = ARENA_WIDTH;
+        let columns = [width * 5 / 8, width * 7 / 8];
+        let title = if self.gamepad { "Gamepad Controls" } else { "Controls" };
+        draw_label(title, 40, width / 2, 40, t.color0);
+        for (slot, x) in columns.iter().enumerate() {
+            draw_label(&format!("Player {}", slot + 1), 20, *x, 85, t.muted);
+        }
+        for (row, action) in Action::ALL.iter().enumerate() {
+            let y = 115 + row as i32 * 26;
+            draw_label(action.name(), 20, width / 4, y, t.color0);
+            for (slot, x) in columns.iter().enumerate() {
+                let selected = row == self.row && slot == self.slot;
+                let text = if selected && self.capturing {
+                    "...".to_string()
+                } else if self.gamepad {
+                    button_name(input.slots[slot].button(*action)).to_string()
+                } else {
+                    key_name(input.slots[slot].key(*action))
+                };
+                let color = if !self.conflicts(input, slot, *action).is_empty() {
+                    RED
+                } else if selected {
+                    t.accent
+                } else {
+                    t.color0
+                };
+                let text = if selected { format!("> {} <", text) } else { text };
+                draw_label(&text, 20, *x, y, color);
+            }
+        }
+        if self.gamepad {
+            let y = 115 + Action::ALL.len() as i32 * 26;
+            draw_label("Gamepad", 20, width / 4, y, t.color0);
+            draw_label("Deadzone", 20, width / 4, y + 26, t.color0);
+            for (slot, x) in columns.iter().enumerate() {
+                let bindings = &input.slots[slot];
+                let pad = match bindings.gamepad {
+                    g if g < 0 => "None".to_string(),
+                    g => format!("#{}{}", g + 1, if bindings.pad().is_some() { "" } else { " (off)" }),
+                };
+                let deadzone = format!("{:.2}", bindings.deadzone);
+                for (i, text) in [pad, deadzone].into_iter().enumerate() {
+                    let selected = slot == self.slot && self.row == Action::ALL.len() + i;
+                    let (text, color) = if selected { (format!("> {} <", text), t.accent) } else { (text, t.muted) };
+                    draw_label(&text, 20, *x, y + i as i32 * 26, color);
+                }
+            }
+        }
+        let conflicts = self.conflicts(input, self.slot, Action::ALL[self.row]);
+        if let Some((slot, action)) = conflicts.first() {
+            draw_text(format!("Also bound to Player {} {}", slot + 1, action.name()), TextPosition::BL, 2, 20, RED);
+        }
+        if self.capturing {
+            let device = if self.gamepad { "button" } else { "key" };
+            draw_text(format!("Press a {}, Escape cancels", device), TextPosition::BL, 1, 20, t.muted);
+        } else if self.gamepad {
+            draw_text("Enter/South bind  Tab/North keyboard".to_string(), TextPosition::BL, 1, 20, t.muted);
+        } else {
+            draw_text("Enter bind  Del clear  Tab gamepad".to_string(), TextPosition::BL, 1, 20, t.muted);
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use raylib_ffi::*;
+use colors::*;
+use crate::rl::camera::CameraRig;
+use crate::rl::particles::{Emitter, Particles};
+use crate::ship::{Ship, ShipEvent};
+use std::f32::consts::PI;
+
+/// Particles alive at once across every effect
+pub const MAX_PARTICLES: usize = 2048;
+
+const FADED: Color = Color { r: 0, g: 0, b: 0, a: 0 };
+
+/// Per second while the engine fires, thrown backwards in a narrow cone
+const EXHAUST: Emitter = Emitter {
+    count: 90, speed: (40.0, 90.0), spread: PI / 6.0, life: (0.15, 0.35),
+    color_start: YELLOW, color_end: Color { r: 230, g: 41, b: 55, a: 0 },
+    size: 1.0, length: 0.0, gravity: false, wrap: true,
+};
+
+const EXPLOSION: Emitter = Emitter {
+    count: 60, speed: (30.0, 160.0), spread: 2.0 * PI, life: (0.3, 0.9),
+    color_start: WHITE, color_end: Color { r: 255, g: 161, b: 0, a: 0 },
+    size: 1.5, length: 0.0, gravity: false, wrap: true,
+};
+
+/// Bits of hull, the colour is swapped for the ship's own
+const DEBRIS: Emitter = Emitter {
+    count: 9, speed: (15.0, 60.0), spread: 2.0 * PI, life: (1.5, 2.5),
+    color_start: WHITE, color_end: FADED,
+    size: 1.0, length: 7.0, gravity: true, wrap: true,
+};
+
+const SPARKS: Emitter = Emitter {
+    count: 8, speed: (20.0, 70.0), spread: 2.0 * PI, life: (0.1, 0.3),
+    color_start: WHITE, color_end: Color { r: 102, g: 191, b: 255, a: 0 },
+    size: 1.0, length: 0.0, gravity: false, wrap: true,
+};
+
+const HIT: Emitter = Emitter {
+    count: 16, speed: (40.0, 120.0), spread: 2.0 * PI, life: (0.15, 0.4),
+    color_start: YELLOW, color_end: FADED,
+    size: 1.0, length: 0.0, gravity: false, wrap: true,
+};
+
+/// How hard the view shakes for each kind of event
+const EXPLODED_SHAKE: f32 = 0.5;
+const DESTROYED_SHAKE: f32 = 0.8;
+const HIT_SHAKE: f32 = 0.15;
+
+/// @brief  Particles and camera shake for something that happened to a ship
+/// @note   `kill_cam` slows time on the ship's final death, left off for survival bots
+///         so a busy wave doesn't keep the game in slow motion
+pub fn emit(particles: &mut Particles, camera: &mut CameraRig, event: ShipEvent, kill_cam: bool) {
+    match event {
+        ShipEvent::Exploded { center, velocity } => {
+            particles.burst(&EXPLOSION, center, 0.0, velocity);
+            camera.shake(EXPLODED_SHAKE);
+        },
+        ShipEvent::Destroyed { center, velocity, color } => {
+            particles.burst(&Emitter { color_start: color, ..DEBRIS }, center, 0.0, velocity);
+            camera.shake(DESTROYED_SHAKE);
+            if kill_cam {
+                camera.kill(center);
+            }
+        },
+        ShipEvent::RocketExpired(center) => particles.burst(&SPARKS, center, 0.0, Vector2 { x: 0.0, y: 0.0 }),
+        ShipEvent::RocketHit(center) => {
+            particles.burst(&HIT, center, 0.0, Vector2 { x: 0.0, y: 0.0 });
+            camera.shake(HIT_SHAKE);
+        },
+    }
+}
+
+/// @brief  Exhaust for a firing engine plus everything that happened to the ship since last time
+pub fn ship_effects(particles: &mut Particles, camera: &mut CameraRig, ship: &mut Ship, kill_cam: bool, delta: f32) {
+    if let Some((rear, direction, velocity)) = ship.exhaust() {
+        particles.stream(&EXHAUST, rear, direction, velocity, delta);
+    }
+    for event in ship.events.drain(..) {
+        emit(particles, camera, event, kill_cam);
+    }
+}
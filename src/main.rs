@@ -1,11 +1,20 @@
-use std::{ffi::{c_void, c_int}, mem};
+use std::{ffi::c_int, path::Path, slice};
 
 use raylib_ffi::*;
 use colors::*;
-use noise::{NoiseFn, Perlin};
 use rand::Rng;
-use rl::{gui::*, vector::vector2};
+use rl::{gui::*, input::{menu_pressed, Action, InputMap, MenuKey}, view::{self, ARENA_WIDTH, ARENA_HEIGHT}, post::{fade_keep, PostChain}, phosphor::{Phosphor, Stroke}, particles::Particles, camera::CameraRig, starfield::Starfield, planetarium::{Planetarium, CATALOG_PATH}};
 use ship::{Ship, Player};
+use rules::{Rules, RulesScreen, FlightModel};
+use powerup::PowerUpField;
+use asteroid::AsteroidField;
+use survival::Survival;
+use save::SaveData;
+use profile::{Profile, Profiles, ProfileScreen};
+use tournament::{Tournament, TournamentSetup, SetupEvent};
+use controls::ControlsScreen;
+use settings::{Settings, SettingsScreen};
+use pause::{PauseScreen, PauseEvent};
 
 pub mod rl {
     pub mod collision;
@@ -16,207 +25,697 @@ pub mod rl {
     pub mod timer;
     pub mod vector;
     pub mod gui;
+    pub mod physics;
+    pub mod config;
+    pub mod input;
+    pub mod view;
+    pub mod post;
+    pub mod phosphor;
+    pub mod particles;
+    pub mod camera;
+    pub mod starfield;
+    pub mod planetarium;
 }
 
 mod ship;
 mod rocket;
+mod rules;
+mod weapon;
+mod powerup;
+mod asteroid;
+mod survival;
+mod save;
+mod profile;
+mod tournament;
+mod controls;
+mod settings;
+mod pause;
+mod effects;
 
-unsafe fn draw_stars(seed: u32) -> RenderTexture2D {
-    let scale: f64 = 0.3;               // scale is inverse, 0 means larger 1 means bigger
-    let dither = 2;                     // the amount of pixels to dither + 1
-    let threshold: f64 = 0.99;
-    let perlin = Perlin::new(seed);
-    
-    let width = GetScreenWidth();
-    let height = GetScreenHeight();
-
-    let render = LoadRenderTexture(width, height);
-
-    BeginTextureMode(render);
-    ClearBackground(BLACK);
-
-    for x in 0..width {
-        for y in 0..height {
-            let value = perlin.get([(x as f64) * scale, (y as f64) * scale]);
-            let alpha_t = ((value + 1.0) / 2.0).clamp(0.0,1.0);
-            let alpha = if alpha_t > threshold && (x*y) % dither == 0 {
-                (255.0 * ((alpha_t - threshold) / (1.0 - threshold))) as u8
-            } else {
-                0
-            };
-            DrawPixel(x, y, Color {r:255,g:255,b:255,a:alpha});
-        }
-    }
-
-    EndTextureMode();
-    render
+#[derive(Clone, Copy)]
+enum MenuAction {
+    OnePlayer,
+    TwoPlayer,
+    Survival,
+    Tournament,
+    Rules,
+    Profiles,
+    Controls,
+    Settings,
+    Stats,
+    Quit,
 }
 
 enum GameState {
     Menu,
     Game,
+    Survival,
+    GameOver,
+    Stats,
+    Profiles,
+    TournamentSetup,
+    Bracket,
+    Controls,
+    Rules,
+    Settings,
+}
+
+/// @brief  Everything that moves in the arena, the layer the vector display keeps glowing
+unsafe fn draw_world(
+    stroke: Stroke, main_star: Vector2, powerups: &PowerUpField, asteroids: &AsteroidField, particles: &Particles, ships: &[&Ship],
+) {
+    DrawCircleV(main_star, 10.0, WHITE);
+    powerups.draw();
+    asteroids.draw();
+    particles.draw(stroke);
+    ships.iter().for_each(|s| s.draw(stroke));
+}
+
+/// @brief  A human ship flown under a profile with that player slot's controls
+unsafe fn new_player(player: Player, rules: &Rules, profile: &Profile, input: &InputMap) -> Ship {
+    let mut ship = Ship::new(player, rules);
+    ship.set_profile(profile);
+    ship.controls = match player {
+        Player::Two => input.slot(1),
+        _ => input.slot(0),
+    };
+    ship
 }
 
-unsafe fn convert_cvoid(value: f32) -> *const c_void {
-    let bytes: [u8; 4] = mem::transmute_copy(&value);
-    bytes.as_ptr() as *const c_void
+/// @brief  A fresh copy of a ship for a restarted round, flown by the same pilot
+unsafe fn restart_ship(ship: &Ship, rules: &Rules, profiles: &Profiles, input: &InputMap) -> Ship {
+    match ship.player {
+        Player::Rob => {
+            let mut rob = Ship::new(Player::Rob, rules);
+            rob.skill = ship.skill;
+            rob
+        },
+        player => {
+            let fallback = profiles.player(if matches!(player, Player::Two) { 1 } else { 0 });
+            new_player(player, rules, profiles.find(&ship.name).unwrap_or(fallback), input)
+        },
+    }
+}
+
+/// @brief  Adds a versus round to the save data, win or loss only counts if it finished
+fn record_versus(save: &mut SaveData, p1: &Ship, p2: &Ship, rules: &Rules, time: f32) {
+    save.profile(&p1.name).record_ship(p1, time);
+    match p2.player {
+        Player::Rob => {
+            if p1.dead || p2.dead {
+                save.profile(&p1.name).record_rob(rules.difficulty, p2.dead);
+            }
+        },
+        _ => save.profile(&p2.name).record_ship(p2, time),
+    }
+    save.save();
+}
+
+fn record_survival(save: &mut SaveData, p1: &Ship, survival: &Survival) {
+    let stats = save.profile(&p1.name);
+    stats.record_ship(p1, survival.time);
+    stats.record_survival(survival);
+    save.save();
+}
+
+/// @brief  The main menu laid out for the current window size in the chosen theme
+unsafe fn main_menu(theme: &Theme) -> Menu<MenuAction> {
+    Menu::new(Button::new_list_centered(
+        -60, 4, 22, 0.0, ARENA_WIDTH as f32, theme.color0, theme.color1,
+        vec![
+            ("1 Player".to_string(), MenuAction::OnePlayer),
+            ("2 Player".to_string(), MenuAction::TwoPlayer),
+            ("Survival".to_string(), MenuAction::Survival),
+            ("Tournament".to_string(), MenuAction::Tournament),
+            ("Rules".to_string(), MenuAction::Rules),
+            ("Profiles".to_string(), MenuAction::Profiles),
+            ("Controls".to_string(), MenuAction::Controls),
+            ("Settings".to_string(), MenuAction::Settings),
+            ("Stats".to_string(), MenuAction::Stats),
+            ("Quit".to_string(), MenuAction::Quit),
+        ]
+    ))
 }
 
 fn main() { unsafe {
-    InitWindow(512, 512, rl_str!("Spacewar!"));
-    let shader = LoadShader(rl_str!("base.vs"), rl_str!("scanlines.fs"));
-    let target = LoadRenderTexture(512, 512);
-    SetTargetFPS(144);
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut settings = Settings::load(&args);
+    SetConfigFlags(settings.config_flags());
+    InitWindow(settings.width, settings.height, rl_str!("Spacewar!"));
+    settings.apply();
+    let mut post = PostChain::load(&save::data_dir().join("post.toml"), ARENA_WIDTH, ARENA_HEIGHT);
+    SetWindowMinSize(ARENA_WIDTH / 2, ARENA_HEIGHT / 2);
+    let target = LoadRenderTexture(ARENA_WIDTH, ARENA_HEIGHT);
+    SetTextureFilter(target.texture, enums::TextureFilter::Bilinear as c_int);
     SetExitKey(0);
     let mut should_exit = false;
-    let mut stars = draw_stars(rand::thread_rng().gen());
+    let mut stars = Starfield::new(ARENA_WIDTH, ARENA_HEIGHT, rand::thread_rng().gen(), settings.star_density);
+    let mut planetarium = Planetarium::load(Path::new(CATALOG_PATH), ARENA_WIDTH, ARENA_HEIGHT);
     let mut game_state = GameState::Menu;
 
     // set our menu data
-    let mut menu_selected: i32 = -1;
-    let mut menu = Button::new_list_centered(
-        -10, 20, 30, 0.0, GetScreenHeight() as f32, WHITE, BLACK,
-        vec![
-            "1 Player".to_string(),
-            "2 Player".to_string(),
-            "Quit".to_string(),
-        ]
-    );
+    let mut menu = main_menu(&settings.theme());
+    let mut menu_theme = settings.theme.clone();
 
     // set our game data
-    let main_star = Vector2 { x: (GetScreenWidth() / 2) as f32, y: (GetScreenHeight() / 2) as f32 };
-    let mut p1 = Ship::new(ship::Player::One);
-    let mut p2 = Ship::new(ship::Player::Rob);
+    let main_star = Vector2 { x: (ARENA_WIDTH / 2) as f32, y: (ARENA_HEIGHT / 2) as f32 };
+    let mut rules = Rules::new();
+    let mut p1 = Ship::new(ship::Player::One, &rules);
+    let mut p2 = Ship::new(ship::Player::Rob, &rules);
+    let mut powerups = PowerUpField::new(&rules);
+    let mut asteroids = AsteroidField::new(&main_star, &rules, &[]);
+    let mut survival = Survival::new();
+    let mut save = SaveData::load();
+    let mut profiles = Profiles::load();
+    let mut profile_screen = ProfileScreen::new(&profiles, settings.theme());
+    let mut tournament: Option<Tournament> = None;
+    let mut tournament_setup = TournamentSetup::new(&profiles, settings.theme());
+    let mut input = InputMap::load(&save::data_dir().join("controls.toml"));
+    let mut controls_screen = ControlsScreen::new(settings.theme());
+    let mut pause: Option<PauseScreen> = None;
+    let mut rules_screen = RulesScreen::new(&rules, settings.theme());
+    let mut settings_screen = SettingsScreen::new(&settings);
+    let mut stats_page: usize = 0;
+    let mut match_time = 0.0;
+    let mut recorded = true;
+    let mut result_armed = false;
 
-    let mut ms = 0.0;
-    let time_loc = GetShaderLocation(shader, rl_str!("time"));
+    let mut phosphor = Phosphor::new(ARENA_WIDTH, ARENA_HEIGHT);
+    let mut particles = Particles::new(effects::MAX_PARTICLES);
+    let mut camera = CameraRig::new(ARENA_WIDTH, ARENA_HEIGHT);
+    let arena = (ARENA_WIDTH as f32, ARENA_HEIGHT as f32);
+    let mut ms: f32 = 0.0;
 
     while !should_exit { 
         if WindowShouldClose() {
             should_exit = true;
         }
-        let delta = delta!();
-        let cursor = GetMousePosition();
-        if IsKeyPressed(key!(F1)) { stars = draw_stars(rand::thread_rng().gen()); }
-        SetShaderValue(shader, time_loc, convert_cvoid(ms), enums::ShaderUniformDataType::Float as c_int);
-        ms += delta;
+        camera.reduce_motion = settings.reduce_motion;
+        camera.auto_zoom = settings.auto_zoom;
+        // the kill cam slows the arena down, the camera and the glow keep real time
+        let real_delta = delta!();
+        let delta = real_delta * camera.time_scale();
+        let cursor = view::mouse_position();
+        if settings.theme != menu_theme {
+            menu = main_menu(&settings.theme());
+            menu_theme = settings.theme.clone();
+        }
+        // only F1 asks for a different sky, a new density rebuilds the same one
+        // and dragging the slider does that every frame, but that's cheap now
+        if IsKeyPressed(key!(F1)) {
+            stars.generate(rand::thread_rng().gen(), settings.star_density);
+        } else if stars.density != settings.star_density {
+            stars.set_density(settings.star_density);
+        }
+        stars.brightness = settings.star_brightness;
+        planetarium.brightness = settings.star_brightness;
+        // without a catalogue to read the generated sky stands in
+        let sky_is_real = settings.planetarium && !planetarium.is_empty();
+        if IsKeyPressed(key!(F11)) {
+            settings.fullscreen = !settings.fullscreen;
+            settings.apply();
+            settings.save();
+        }
+        // a window dragged to a new size is remembered as the windowed resolution
+        if IsWindowResized() && !IsWindowFullscreen() {
+            settings.width = GetScreenWidth();
+            settings.height = GetScreenHeight();
+        }
+        post.set("time", ms);
+        post.set("crt_intensity", settings.shader_intensity);
+        post.set("keep", fade_keep(settings.persistence, real_delta));
+        // the crt flicker holds still along with everything else while paused
+        if pause.is_none() {
+            ms += delta;
+            if !settings.reduce_motion {
+                match sky_is_real {
+                    true => planetarium.update(delta),
+                    false => stars.update(delta),
+                }
+            }
+        }
 
         match &game_state {
             GameState::Menu => {
-                let prev_selected = menu_selected;
-                if IsKeyPressed(key!(Up)) || IsKeyPressed(key!(W)) {
-                    menu_selected -= 1;
-                    if menu_selected < 0 {
-                        menu_selected = menu.len() as i32 - 1;
-                    }
+                if IsKeyPressed(key!(F2)) {
+                    rules.classic = !rules.classic;
                 }
-                if IsKeyPressed(key!(Down)) || IsKeyPressed(key!(S)) {
-                    menu_selected += 1;
-                    if menu_selected >= menu.len() as i32 {
-                        menu_selected = 0;
-                    }
+                if IsKeyPressed(key!(F3)) {
+                    rules.integrator = rules.integrator.next();
                 }
-                if IsKeyPressed(key!(Escape)) {
-                    menu_selected = -1;
+                if IsKeyPressed(key!(F6)) {
+                    rules.difficulty = rules.difficulty.next();
                 }
-                if menu_selected != prev_selected {
-                    for i in 0..menu.len() {
-                        menu[i].select(false);
+                if IsKeyPressed(key!(F5)) {
+                    rules.asteroids = !rules.asteroids;
+                }
+                if IsKeyPressed(key!(F7)) {
+                    settings.planetarium = !settings.planetarium;
+                    settings.save();
+                }
+                if IsKeyPressed(key!(F4)) {
+                    rules.flight_model = match rules.flight_model {
+                        FlightModel::Arcade => FlightModel::Inertial,
+                        FlightModel::Inertial => FlightModel::Arcade,
+                    };
+                }
+                if let Some(action) = menu.update(&cursor) {
+                    match action {
+                        MenuAction::OnePlayer => {
+                            game_state = GameState::Game;
+                            p1 = new_player(Player::One, &rules, profiles.player(0), &input);
+                            p2 = Ship::new(ship::Player::Rob, &rules);
+                            p2.skill = rules.difficulty.skill();
+                            powerups = PowerUpField::new(&rules);
+                            match_time = 0.0;
+                            recorded = false;
+                            pause = None;
+                            asteroids = AsteroidField::new(&main_star, &rules, &[p1.center, p2.center]);
+                        }
+                        MenuAction::TwoPlayer => {
+                            game_state = GameState::Game;
+                            p1 = new_player(Player::One, &rules, profiles.player(0), &input);
+                            p2 = new_player(Player::Two, &rules, profiles.player(1), &input);
+                            powerups = PowerUpField::new(&rules);
+                            match_time = 0.0;
+                            recorded = false;
+                            pause = None;
+                            asteroids = AsteroidField::new(&main_star, &rules, &[p1.center, p2.center]);
+                        },
+                        MenuAction::Survival => {
+                            game_state = GameState::Survival;
+                            p1 = new_player(Player::One, &rules, profiles.player(0), &input);
+                            survival = Survival::new();
+                            recorded = false;
+                            pause = None;
+                            powerups = PowerUpField::new(&rules);
+                            asteroids = AsteroidField::new(&main_star, &rules, &[p1.center]);
+                        },
+                        MenuAction::Tournament => {
+                            game_state = GameState::TournamentSetup;
+                            tournament_setup = TournamentSetup::new(&profiles, settings.theme());
+                        },
+                        MenuAction::Rules => {
+                            game_state = GameState::Rules;
+                            rules_screen = RulesScreen::new(&rules, settings.theme());
+                        },
+                        MenuAction::Controls => {
+                            game_state = GameState::Controls;
+                            controls_screen = ControlsScreen::new(settings.theme());
+                        },
+                        MenuAction::Settings => {
+                            game_state = GameState::Settings;
+                            settings_screen = SettingsScreen::new(&settings);
+                        },
+                        MenuAction::Profiles => {
+                            game_state = GameState::Profiles;
+                            profile_screen = ProfileScreen::new(&profiles, settings.theme());
+                        },
+                        MenuAction::Stats => {
+                            game_state = GameState::Stats;
+                            stats_page = 0;
+                        },
+                        MenuAction::Quit => should_exit = true,
                     }
                 }
-                if menu_selected != -1 {
-                    let k_selected = &mut menu[menu_selected as usize];
-                    k_selected.select(true);
-                    if IsKeyPressed(key!(Enter)) || IsKeyPressed(key!(Space)) {
-                        match k_selected.label.as_str() {
-                            "1 Player" => {
-                                game_state = GameState::Game;
-                                p2 = Ship::new(ship::Player::Rob);
+            },
+            GameState::Game => {
+                let decided = p1.dead || p2.dead;
+                // accept and back share buttons with fire and the weapon switch, so leaving a
+                // decided round waits out the kill cam and a fresh press after every control is let go
+                if pause.is_some() || !decided {
+                    result_armed = false;
+                } else if !camera.kill_cam() && !p1.controls.any_down() && !p2.controls.any_down() {
+                    result_armed = true;
+                }
+                let leave = result_armed && (menu_pressed(MenuKey::Accept) || menu_pressed(MenuKey::Back));
+                let pause_pressed = p1.controls.pressed(Action::Pause) || p2.controls.pressed(Action::Pause);
+                let event = match pause.as_mut() {
+                    Some(_) if pause_pressed => Some(PauseEvent::Resume),
+                    Some(screen) => screen.update(&mut settings, &cursor),
+                    None if leave => Some(PauseEvent::Quit),
+                    None => {
+                        let escaped = !decided && (IsKeyPressed(key!(Backspace)) || IsKeyPressed(key!(Escape)));
+                        if pause_pressed || escaped {
+                            let quit = if tournament.is_some() { "Quit to Bracket" } else { "Quit to Menu" };
+                            pause = Some(PauseScreen::new(quit, settings.theme(), decided));
+                        }
+                        None
+                    },
+                };
+                match event {
+                    Some(PauseEvent::Resume) => pause = None,
+                    Some(PauseEvent::Restart) => {
+                        if !recorded {
+                            record_versus(&mut save, &p1, &p2, &rules, match_time);
+                        }
+                        p1 = restart_ship(&p1, &rules, &profiles, &input);
+                        p2 = restart_ship(&p2, &rules, &profiles, &input);
+                        particles.clear();
+                        camera.reset();
+                        powerups = PowerUpField::new(&rules);
+                        asteroids = AsteroidField::new(&main_star, &rules, &[p1.center, p2.center]);
+                        match_time = 0.0;
+                        recorded = false;
+                        pause = None;
+                    },
+                    Some(PauseEvent::Quit) => {
+                        if !recorded {
+                            record_versus(&mut save, &p1, &p2, &rules, match_time);
+                            recorded = true;
+                        }
+                        // leaving a tournament game early replays it instead of forfeiting, and
+                        // both ships lost on the same frame is a draw that gets replayed too
+                        if let Some(t) = tournament.as_mut() {
+                            if p1.dead != p2.dead {
+                                if let Some((winner, loser)) = t.report(p2.dead) {
+                                    profiles.record_match(&winner, &loser);
+                                }
                             }
-                            "2 Player" => {
-                                game_state = GameState::Game;
-                                p2 = Ship::new(ship::Player::Two);
-                            },
-                            "Quit" => should_exit = true,
-                            _ => {} 
                         }
+                        game_state = match tournament {
+                            Some(_) => GameState::Bracket,
+                            None => GameState::Menu,
+                        };
+                        p1 = Ship::new(ship::Player::One, &rules);
+                        p2 = Ship::new(ship::Player::Rob, &rules);
+                        result_armed = false;
+                        pause = None;
+                    },
+                    None => {},
+                }
+                if pause.is_none() {
+                    if !p1.dead && !p2.dead {
+                        match_time += delta;
+                    } else if !recorded {
+                        record_versus(&mut save, &p1, &p2, &rules, match_time);
+                        recorded = true;
+                    }
+                    if !p1.dead { p1.update(&main_star, slice::from_mut(&mut p2), &rules, delta); }
+                    if !p2.dead { p2.update(&main_star, slice::from_mut(&mut p1), &rules, delta); }
+                    powerups.update(&main_star, &rules, delta);
+                    powerups.collect(&mut p1, &rules);
+                    powerups.collect(&mut p2, &rules);
+                    asteroids.update(&main_star, &rules, &[p1.center, p2.center], delta);
+                    asteroids.shoot(&mut p1);
+                    asteroids.shoot(&mut p2);
+                    if asteroids.hits(&p1) { p1.crash(&main_star, &[p2.center], &rules); }
+                    if asteroids.hits(&p2) { p2.crash(&main_star, &[p1.center], &rules); }
+                    effects::ship_effects(&mut particles, &mut camera, &mut p1, true, delta);
+                    effects::ship_effects(&mut particles, &mut camera, &mut p2, true, delta);
+                    particles.update(&main_star, arena, delta);
+                }
+            },
+            GameState::Survival => {
+                let pause_pressed = p1.controls.pressed(Action::Pause);
+                let event = match pause.as_mut() {
+                    Some(_) if pause_pressed => Some(PauseEvent::Resume),
+                    Some(screen) => screen.update(&mut settings, &cursor),
+                    None => {
+                        if pause_pressed || IsKeyPressed(key!(Backspace)) || IsKeyPressed(key!(Escape)) {
+                            pause = Some(PauseScreen::new("Quit to Menu", settings.theme(), false));
+                        }
+                        None
+                    },
+                };
+                match event {
+                    Some(PauseEvent::Resume) => pause = None,
+                    Some(PauseEvent::Restart) => {
+                        if !recorded {
+                            record_survival(&mut save, &p1, &survival);
+                        }
+                        p1 = restart_ship(&p1, &rules, &profiles, &input);
+                        survival = Survival::new();
+                        particles.clear();
+                        camera.reset();
+                        powerups = PowerUpField::new(&rules);
+                        asteroids = AsteroidField::new(&main_star, &rules, &[p1.center]);
+                        recorded = false;
+                        pause = None;
+                    },
+                    Some(PauseEvent::Quit) => {
+                        if !recorded {
+                            record_survival(&mut save, &p1, &survival);
+                            recorded = true;
+                        }
+                        game_state = GameState::Menu;
+                        pause = None;
+                    },
+                    None => {},
+                }
+                if pause.is_none() {
+                    if !p1.dead { p1.update(&main_star, &mut survival.bots, &rules, delta); }
+                    survival.update(&main_star, &mut p1, &rules, delta);
+                    let bot_centers: Vec<Vector2> = survival.bots.iter().map(|b| b.center).collect();
+                    powerups.update(&main_star, &rules, delta);
+                    powerups.collect(&mut p1, &rules);
+                    asteroids.update(&main_star, &rules, &[p1.center], delta);
+                    asteroids.shoot(&mut p1);
+                    if asteroids.hits(&p1) { p1.crash(&main_star, &bot_centers, &rules); }
+                    for bot in survival.bots.iter_mut() {
+                        asteroids.shoot(bot);
+                        if asteroids.hits(bot) { bot.crash(&main_star, &[p1.center], &rules); }
+                    }
+                    effects::ship_effects(&mut particles, &mut camera, &mut p1, true, delta);
+                    for bot in survival.bots.iter_mut() {
+                        effects::ship_effects(&mut particles, &mut camera, bot, false, delta);
+                    }
+                    for event in survival.wrecks.drain(..) {
+                        effects::emit(&mut particles, &mut camera, event, false);
                     }
+                    particles.update(&main_star, arena, delta);
                 }
-                // mouse handler
-                /*
-                let selected = menu.iter().find(
-                    |b| b.within(&cursor) && IsMouseButtonPressed(mouse_b!(Left)));
-                if selected.is_some() { 
-                    match selected.unwrap().label.as_str() {
-                        "1 Player" => game_state = GameState::Game,
-                        "2 Player" => game_state = GameState::Game,
-                        "Quit" => should_exit = true,
-                        _ => {}
+                if survival.finished() {
+                    if !recorded {
+                        record_survival(&mut save, &p1, &survival);
+                        recorded = true;
                     }
+                    game_state = GameState::GameOver;
                 }
-                */
             },
-            GameState::Game => {
-                if IsKeyPressed(key!(Backspace)) || IsKeyPressed(key!(Escape)) {
+            GameState::GameOver => {
+                if menu_pressed(MenuKey::Accept) || menu_pressed(MenuKey::Back) {
+                    game_state = GameState::Menu;
+                }
+            },
+            GameState::Stats => {
+                if menu_pressed(MenuKey::Accept) || menu_pressed(MenuKey::Back) {
+                    game_state = GameState::Menu;
+                }
+                let pages = save.profiles.len().max(1);
+                if menu_pressed(MenuKey::Right) {
+                    stats_page = (stats_page + 1) % pages;
+                }
+                if menu_pressed(MenuKey::Left) {
+                    stats_page = (stats_page + pages - 1) % pages;
+                }
+            },
+            GameState::Rules => {
+                if rules_screen.update(&mut rules, &cursor) {
+                    game_state = GameState::Menu;
+                }
+            },
+            GameState::Settings => {
+                if settings_screen.update(&mut settings, &cursor) {
+                    game_state = GameState::Menu;
+                }
+            },
+            GameState::Controls => {
+                if controls_screen.update(&mut input) {
                     game_state = GameState::Menu;
-                    p1 = Ship::new(ship::Player::One);
-                    p2 = Ship::new(ship::Player::Rob);
                 }
-                if !p1.dead { p1.update(&main_star, &p2, delta); }
-                if !p2.dead { p2.update(&main_star, &p1, delta); }
+            },
+            GameState::Profiles => {
+                if profile_screen.update(&mut profiles, &cursor) {
+                    game_state = GameState::Menu;
+                }
+            },
+            GameState::TournamentSetup => {
+                match tournament_setup.update(&profiles) {
+                    Some(SetupEvent::Back) => game_state = GameState::Menu,
+                    Some(SetupEvent::Start(t)) => {
+                        tournament = Some(t);
+                        game_state = GameState::Bracket;
+                    },
+                    None => {},
+                }
+            },
+            GameState::Bracket => {
+                let t = tournament.as_ref().unwrap();
+                if menu_pressed(MenuKey::Back) {
+                    tournament = None;
+                    game_state = GameState::Menu;
+                } else if menu_pressed(MenuKey::Accept) {
+                    match t.next_match() {
+                        Some((a, b)) => {
+                            game_state = GameState::Game;
+                            p1 = new_player(Player::One, &rules, profiles.find(a).unwrap_or(profiles.player(0)), &input);
+                            p2 = new_player(Player::Two, &rules, profiles.find(b).unwrap_or(profiles.player(1)), &input);
+                            powerups = PowerUpField::new(&rules);
+                            match_time = 0.0;
+                            recorded = false;
+                            pause = None;
+                            asteroids = AsteroidField::new(&main_star, &rules, &[p1.center, p2.center]);
+                        },
+                        None => {
+                            tournament = None;
+                            game_state = GameState::Menu;
+                        },
+                    }
+                }
             }
         }
-        
+
+        let ships: Vec<&Ship> = match game_state {
+            GameState::Game => vec![&p1, &p2],
+            GameState::Survival => std::iter::once(&p1).chain(survival.bots.iter()).collect(),
+            _ => vec![],
+        };
+        // the vector display draws the arena on its own so it can glow, before the frame starts
+        if ships.is_empty() {
+            particles.clear();
+            camera.reset();
+        } else if pause.is_none() {
+            let centers: Vec<Vector2> = ships.iter().filter(|s| !s.dead).map(|s| s.center).collect();
+            camera.update(&centers, real_delta);
+        }
+        let trails = match settings.vector_display && !ships.is_empty() {
+            true => {
+                phosphor.begin();
+                camera.begin();
+                draw_world(Stroke::Beam, main_star, &powerups, &asteroids, &particles, &ships);
+                camera.end();
+                Some(phosphor.end(settings.persistence, real_delta))
+            },
+            false => {
+                phosphor.clear();
+                None
+            },
+        };
+
         BeginTextureMode(target);
             ClearBackground(BLACK);
-            DrawTexture(stars.texture, 0, 0, WHITE);
+            match sky_is_real {
+                true => planetarium.draw(),
+                false => stars.draw(),
+            }
+            match trails {
+                Some(texture) => Phosphor::draw(texture),
+                None => {
+                    camera.begin();
+                    draw_world(Stroke::Line, main_star, &powerups, &asteroids, &particles, &ships);
+                    camera.end();
+                },
+            }
+            let theme = settings.theme();
             match &game_state {
                 GameState::Menu => {
-                    menu.iter().for_each(|b| b.draw(&cursor));
-                    draw_label("Spacewar!", 60, GetScreenWidth() / 2, GetScreenHeight() / 2 - 80, WHITE);
+                    menu.draw();
+                    draw_label("Spacewar!", 60, ARENA_WIDTH / 2, ARENA_HEIGHT / 2 - 130, theme.color0);
+                    if rules.classic {
+                        draw_text("Classic Rules".to_string(), TextPosition::BR, 4, 20, theme.color0);
+                    }
+                    if rules.asteroids {
+                        draw_text("Asteroid Field".to_string(), TextPosition::BR, 3, 20, theme.color0);
+                    }
+                    if rules.flight_model == FlightModel::Inertial {
+                        draw_text("Inertial Flight".to_string(), TextPosition::BR, 2, 20, theme.color0);
+                    }
+                    draw_text(rules.integrator.name().to_string(), TextPosition::BR, 1, 20, theme.muted);
+                    if sky_is_real {
+                        draw_text("Expensive Planetarium".to_string(), TextPosition::BR, 5, 20, theme.color0);
+                    }
+                    draw_text(format!("Rob: {}", rules.difficulty.name()), TextPosition::BL, 1, 20, theme.muted);
+                    for slot in 0..input.slots.len() {
+                        draw_text(format!("P{}: {}", slot + 1, input.describe(slot)), TextPosition::TL, slot as i32 + 1, 20, theme.muted);
+                    }
                 },
                 GameState::Game => {
-                    DrawCircleV(main_star, 10.0, WHITE);
-                    p1.draw();
-                    p2.draw();
+                    p1.draw_hud(&rules, TextPosition::BL);
+                    p2.draw_hud(&rules, TextPosition::BR);
+                    if let Some(t) = &tournament {
+                        t.draw_score();
+                    }
+                    if result_armed {
+                        draw_label("Press Enter", 20, ARENA_WIDTH / 2, ARENA_HEIGHT / 2 + 50, theme.color0);
+                    }
                     match p2.player {
                         Player::Rob => {
                             if p1.dead {
-                                draw_label("Game Over.", 60, GetScreenWidth() / 2, GetScreenHeight() / 2, RED);
+                                draw_label("Game Over.", 60, ARENA_WIDTH / 2, ARENA_HEIGHT / 2, RED);
                             }
                             else if p2.dead {
-                                draw_label("You Win!", 60, GetScreenWidth() / 2, GetScreenHeight() / 2, GREEN);
+                                draw_label("You Win!", 60, ARENA_WIDTH / 2, ARENA_HEIGHT / 2, p1.color);
                             }
                         },
                         _ => {
                             if p1.dead {
-                                draw_label("Player 2 Wins!", 60, GetScreenWidth() / 2, GetScreenHeight() / 2, GOLD);
+                                draw_label(&format!("{} Wins!", p2.name), 60, ARENA_WIDTH / 2, ARENA_HEIGHT / 2, p2.color);
                             } else if p2.dead {
-                                draw_label("Player 1 Wins!", 60, GetScreenWidth() / 2, GetScreenHeight() / 2, GREEN);
+                                draw_label(&format!("{} Wins!", p1.name), 60, ARENA_WIDTH / 2, ARENA_HEIGHT / 2, p1.color);
                             }
                         }
                     }
+                },
+                GameState::Survival => {
+                    survival.draw();
+                    p1.draw_hud(&rules, TextPosition::BL);
+                },
+                GameState::GameOver => {
+                    survival.draw_game_over();
+                },
+                GameState::Stats => {
+                    match save.profiles.get(stats_page) {
+                        Some(stats) => stats.draw(ARENA_WIDTH / 2, 100, &theme),
+                        None => draw_label("No games played yet.", 30, ARENA_WIDTH / 2, ARENA_HEIGHT / 2, theme.muted),
+                    }
+                    if save.profiles.len() > 1 {
+                        draw_text(format!("< {} / {} >", stats_page + 1, save.profiles.len()), TextPosition::BR, 1, 20, theme.muted);
+                    }
+                },
+                GameState::Profiles => {
+                    profile_screen.draw(&profiles);
+                },
+                GameState::Controls => {
+                    controls_screen.draw(&input);
+                },
+                GameState::Rules => {
+                    rules_screen.draw();
+                },
+                GameState::Settings => {
+                    settings_screen.draw();
+                },
+                GameState::TournamentSetup => {
+                    tournament_setup.draw(&profiles);
+                },
+                GameState::Bracket => {
+                    if let Some(t) = &tournament {
+                        t.draw(&profiles);
+                    }
                 }
             }
+            if let Some(screen) = &pause {
+                screen.draw();
+            }
         EndTextureMode();
 
+        // with the crt effect turned all the way down the chain is skipped entirely
+        let frame = match settings.shader_intensity > 0.0 {
+            true => post.apply(target.texture),
+            false => target.texture,
+        };
+
         BeginDrawing();
         ClearBackground(BLACK);
-        BeginShaderMode(shader);
-        DrawTextureRec(
-            target.texture, 
-            Rectangle {
-                x: 0.0, y: 0.0,
-                width: 512.0,
-                height: -512.0,
-            },
-            vector2::zero(),
-            WHITE
-        );
-        EndShaderMode();
-        draw_fps(TextPosition::TL, 0);
+        view::draw_arena(frame);
+        if settings.show_fps {
+            draw_fps(TextPosition::TL, 0);
+        }
         EndDrawing();
     }
-    UnloadShader(shader);
+    stars.unload();
+    UnloadRenderTexture(target);
+    post.unload();
+    phosphor.unload();
     CloseWindow();
 }}
@@ -0,0 +1,144 @@
+use raylib_ffi::*;
+use crate::rl::gui::{draw_label, Button, Menu, Theme};
+use crate::rl::input::{menu_pressed, MenuKey};
+use crate::settings::{Settings, SettingsScreen};
+use crate::rl::view::{ARENA_WIDTH, ARENA_HEIGHT};
+
+#[derive(Clone, Copy, PartialEq)]
+enum PauseAction {
+    Resume,
+    Restart,
+    Settings,
+    Quit,
+}
+
+pub enum PauseEvent {
+    Resume,
+    Restart,
+    Quit,
+}
+
+enum Page {
+    Menu,
+    Confirm(PauseAction),
+    Settings(SettingsScreen),
+}
+
+/// The overlay shown over a paused match, anything that throws a round still
+/// being played away has to be confirmed first
+pub struct PauseScreen {
+    menu: Menu<PauseAction>,
+    confirm: Menu<bool>,
+    page: Page,
+    quit_label: String,
+    theme: Theme,
+    /// someone has already won, so there's nothing left to lose by leaving
+    round_over: bool,
+}
+
+impl PauseScreen {
+    /// @brief  `quit_label` names where quitting leaves to, the bracket during a tournament
+    pub unsafe fn new(quit_label: &str, theme: Theme, round_over: bool) -> PauseScreen {
+        let mut screen = PauseScreen {
+            menu: Menu::new(vec![]),
+            confirm: Menu::new(vec![]),
+            page: Page::Menu,
+            quit_label: quit_label.to_string(),
+            theme,
+            round_over,
+        };
+        screen.layout();
+        screen
+    }
+
+    unsafe fn layout(&mut self) {
+        let (width, t) = (ARENA_WIDTH as f32, self.theme);
+        self.menu = Menu::new(Button::new_list_centered(
+            -20, 6, 22, 0.0, width, t.color0, t.color1,
+            vec![
+                ("Resume".to_string(), PauseAction::Resume),
+                ("Restart Round".to_string(), PauseAction::Restart),
+                ("Settings".to_string(), PauseAction::Settings),
+                (self.quit_label.clone(), PauseAction::Quit),
+            ]
+        ));
+        self.menu.select(Some(0));
+        self.confirm = Menu::new(Button::new_row(
+            0.42, vec![("Yes".to_string(), true), ("No".to_string(), false)], 22, 0.0, width, t.color0, t.color1,
+        ));
+        self.confirm.select(Some(1));
+    }
+
+    pub unsafe fn update(&mut self, settings: &mut Settings, cursor: &Vector2) -> Option<PauseEvent> {
+        match &mut self.page {
+            Page::Settings(screen) => {
+                if screen.update(settings, cursor) {
+                    self.page = Page::Menu;
+                    // a theme picked in settings shows straight away
+                    if settings.theme != self.theme.name {
+                        self.theme = settings.theme();
+                        self.layout();
+                    }
+                }
+                None
+            },
+            Page::Confirm(action) => {
+                let action = *action;
+                if menu_pressed(MenuKey::Back) {
+                    self.page = Page::Menu;
+                    return None
+                }
+                match self.confirm.update(cursor) {
+                    Some(true) if action == PauseAction::Restart => Some(PauseEvent::Restart),
+                    Some(true) => Some(PauseEvent::Quit),
+                    Some(false) => {
+                        self.page = Page::Menu;
+                        None
+                    },
+                    None => None,
+                }
+            },
+            Page::Menu => {
+                if menu_pressed(MenuKey::Back) {
+                    return Some(PauseEvent::Resume)
+                }
+                match self.menu.update(cursor) {
+                    Some(PauseAction::Resume) => Some(PauseEvent::Resume),
+                    Some(PauseAction::Settings) => {
+                        self.page = Page::Settings(SettingsScreen::new(settings));
+                        None
+                    },
+                    Some(PauseAction::Restart) if self.round_over => Some(PauseEvent::Restart),
+                    Some(PauseAction::Quit) if self.round_over => Some(PauseEvent::Quit),
+                    Some(action) => {
+                        self.confirm.select(Some(1));
+                        self.page = Page::Confirm(action);
+                        None
+                    },
+                    None => None,
+                }
+            },
+        }
+    }
+
+    pub unsafe fn draw(&self) {
+        let (width, height, t) = (ARENA_WIDTH, ARENA_HEIGHT, &self.theme);
+        DrawRectangle(0, 0, width, height, Color { a: 200, ..t.color1 });
+        match &self.page {
+            Page::Settings(screen) => screen.draw(),
+            Page::Confirm(action) => {
+                let question = match action {
+                    PauseAction::Restart => "Restart this round?",
+                    _ => "Abandon this match?",
+                };
+                draw_label(question, 30, width / 2, height / 2 - 60, t.color0);
+                draw_label("Progress in this round will be lost", 20, width / 2, height / 2 - 25, t.muted);
+                self.confirm.draw();
+            },
+            Page::Menu => {
+                draw_label("Paused", 40, width / 2, height / 2 - 90, t.color0);
+                self.menu.draw();
+            },
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use raylib_ffi::*;
+use colors::*;
+use crate::rl::gui::draw_label;
+use crate::rl::physics::{self, Body};
+use crate::rl::timer::Timer;
+use crate::rl::vector::vector2 as v;
+use crate::rules::Rules;
+use crate::ship::Ship;
+use crate::rl::view::{ARENA_WIDTH, ARENA_HEIGHT};
+use std::f32::consts::PI;
+use rand::Rng;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum PowerUpKind {
+    RapidFire,
+    Fuel,
+    Shield,
+    Hyperspace,
+    Spread,
+}
+
+impl PowerUpKind {
+    pub fn letter(&self) -> &'static str {
+        match self {
+            PowerUpKind::RapidFire => "R",
+            PowerUpKind::Fuel => "F",
+            PowerUpKind::Shield => "S",
+            PowerUpKind::Hyperspace => "H",
+            PowerUpKind::Spread => "W",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            PowerUpKind::RapidFire => ORANGE,
+            PowerUpKind::Fuel => YELLOW,
+            PowerUpKind::Shield => SKYBLUE,
+            PowerUpKind::Hyperspace => PURPLE,
+            PowerUpKind::Spread => PINK,
+        }
+    }
+}
+
+pub struct PowerUp {
+    pub kind: PowerUpKind,
+    pub center: Vector2,
+    velocity: Vector2,
+    pub life: f32,
+}
+
+impl PowerUp {
+    /// @brief  Places a pickup on a roughly circular orbit around the star
+    pub unsafe fn spawn(kind: PowerUpKind, main_star: &Vector2) -> PowerUp {
+        let mut rng = rand::thread_rng();
+        let radius = rng.gen_range(80.0..200.0);
+        let angle = rng.gen_range(0.0..2.0 * PI);
+        let direction = if rng.gen_bool(0.5) { PI / 2.0 } else { -PI / 2.0 };
+        PowerUp {
+            kind,
+            center: v::add(main_star, &v::from_angle(angle, radius)),
+            velocity: v::from_angle(angle + direction, physics::orbit_speed(radius)),
+            life: 20.0,
+        }
+    }
+
+    pub unsafe fn update(&mut self, main_star: &Vector2, rules: &Rules, delta: f32) {
+        let mut body = Body { position: self.center, velocity: self.velocity };
+        physics::integrate(&mut body, main_star, &v::zero(), rules.integrator, delta);
+        self.velocity = body.velocity;
+        self.center = v::wrap(&body.position, ARENA_WIDTH as f32, ARENA_HEIGHT as f32);
+        self.life -= delta;
+    }
+
+    pub fn touches(&self, ship: &Ship) -> bool {
+        v::dist(&self.center, &ship.center) < 16.0
+    }
+
+    pub unsafe fn draw(&self) {
+        // blink out over the last few seconds
+        if self.life < 3.0 && (self.life * 6.0) as i32 % 2 == 0 {
+            return
+        }
+        let color = self.kind.color();
+        DrawCircleLines(self.center.x as i32, self.center.y as i32, 8.0, color);
+        draw_label(self.kind.letter(), 10, self.center.x as i32, self.center.y as i32, color);
+    }
+}
+
+/// Spawns pickups on a timer and hands them to ships that fly through them
+pub struct PowerUpField {
+    pub pickups: Vec<PowerUp>,
+    spawn_timer: Timer,
+}
+
+impl PowerUpField {
+    pub fn new(rules: &Rules) -> PowerUpField {
+        let mut spawn_timer = Timer::new(rules.powerup_interval);
+        spawn_timer.start();
+        PowerUpField {
+            pickups: vec![],
+            spawn_timer,
+        }
+    }
+
+    pub unsafe fn update(&mut self, main_star: &Vector2, rules: &Rules, delta: f32) {
+        let kinds = rules.power_ups();
+        self.spawn_timer.update(delta);
+        if !kinds.is_empty() && !self.spawn_timer.is_running() {
+            if self.pickups.len() < rules.max_powerups {
+                let kind = kinds[rand::thread_rng().gen_range(0..kinds.len())];
+                self.pickups.push(PowerUp::spawn(kind, main_star));
+            }
+            self.spawn_timer.start();
+        }
+        self.pickups.iter_mut().for_each(|p| p.update(main_star, rules, delta));
+        self.pickups.retain(|p| p.life > 0.0 && !CheckCollisionPointCircle(p.center, *main_star, 5.0));
+    }
+
+    pub fn collect(&mut self, ship: &mut Ship, rules: &Rules) {
+        if ship.dead { return }
+        self.pickups.retain(|p| {
+            if p.touches(ship) {
+                ship.power_up(p.kind, rules);
+                return false
+            }
+            true
+        });
+    }
+
+    pub unsafe fn draw(&self) {
+        self.pickups.iter().for_each(|p| p.draw());
+    }
+}
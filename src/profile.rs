@@ -0,0 +1,316 @@
+use raylib_ffi::*;
+use colors::*;
+use crate::key;
+use crate::rl::config::Config;
+use crate::rl::gui::{draw_label, Layout, Panel, Theme, Widget};
+use crate::rl::input::{menu_pressed, MenuKey};
+use crate::save::data_dir;
+use crate::rl::view::{ARENA_WIDTH, ARENA_HEIGHT};
+use std::path::PathBuf;
+
+/// Hull colours a profile can cycle through, red is left to Rob
+pub const PALETTE: [Color; 8] = [GREEN, GOLD, SKYBLUE, ORANGE, PINK, PURPLE, LIME, BEIGE];
+
+const MAX_NAME_LEN: usize = 12;
+
+/// Rating every new profile starts on, and how far a single match can move it
+const START_ELO: f32 = 1000.0;
+const ELO_K: f32 = 32.0;
+
+#[derive(Clone)]
+pub struct Profile {
+    pub name: String,
+    pub color: Color,
+    pub elo: f32,
+}
+
+impl Profile {
+    pub fn new(name: &str, color: Color) -> Profile {
+        Profile { name: name.to_string(), color, elo: START_ELO }
+    }
+
+    pub fn next_color(&mut self) {
+        let i = PALETTE.iter()
+            .position(|c| c.r == self.color.r && c.g == self.color.g && c.b == self.color.b)
+            .map_or(0, |i| (i + 1) % PALETTE.len());
+        self.color = PALETTE[i];
+    }
+}
+
+fn color_to_hex(color: &Color) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b)
+}
+
+fn color_from_hex(hex: &str) -> Option<Color> {
+    let hex = hex.strip_prefix('#')?;
+    if hex.len() != 6 { return None }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some(Color { r: channel(0)?, g: channel(2)?, b: channel(4)?, a: 255 })
+}
+
+/// Every local profile and which of them is flying each ship
+pub struct Profiles {
+    pub list: Vec<Profile>,
+    pub selected: [usize; 2],
+    path: PathBuf,
+}
+
+impl Profiles {
+    pub fn load() -> Profiles {
+        let path = data_dir().join("profiles.toml");
+        let mut profiles = Profiles { list: vec![], selected: [0, 1], path };
+        if let Some(config) = Config::load(&profiles.path) {
+            for section in config.sections() {
+                if !section.starts_with("profile.") { continue }
+                let name = match config.get_str(&section, "name") {
+                    Some(name) if !name.trim().is_empty() => name,
+                    _ => continue,
+                };
+                let color = config.get_str(&section, "color")
+                    .and_then(|c| color_from_hex(&c))
+                    .unwrap_or(PALETTE[profiles.list.len() % PALETTE.len()]);
+                if !profiles.list.iter().any(|p| p.name == name) {
+                    let mut profile = Profile::new(&name, color);
+                    profile.elo = config.get_or(&section, "elo", START_ELO);
+                    profiles.list.push(profile);
+                }
+            }
+            profiles.selected = [
+                config.get_or("", "player_1", 0),
+                config.get_or("", "player_2", 1),
+            ];
+        }
+        if profiles.list.is_empty() {
+            profiles.list.push(Profile::new("Player 1", GREEN));
+        }
+        if profiles.list.len() < 2 {
+            profiles.list.push(Profile::new("Player 2", GOLD));
+        }
+        for slot in profiles.selected.iter_mut() {
+            if *slot >= profiles.list.len() { *slot = 0 }
+        }
+        profiles
+    }
+
+    pub fn save(&self) {
+        let mut config = Config::new();
+        config.set("", "player_1", self.selected[0]);
+        config.set("", "player_2", self.selected[1]);
+        for (i, profile) in self.list.iter().enumerate() {
+            let section = format!("profile.{}", i);
+            config.set_str(&section, "name", &profile.name);
+            config.set_str(&section, "color", &color_to_hex(&profile.color));
+            config.set(&section, "elo", profile.elo);
+        }
+        if let Err(e) = config.save(&self.path) {
+            eprintln!("could not save profiles to {}: {}", self.path.display(), e);
+        }
+    }
+
+    /// @brief  The profile flying the given ship, 0 for player one and 1 for player two
+    pub fn player(&self, slot: usize) -> &Profile {
+        &self.list[self.selected[slot]]
+    }
+
+    pub fn find(&self, name: &str) -> Option<&Profile> {
+        self.list.iter().find(|p| p.name == name)
+    }
+
+    pub fn elo(&self, name: &str) -> f32 {
+        self.find(name).map_or(START_ELO, |p| p.elo)
+    }
+
+    /// @brief  Moves both ratings by the usual Elo update after a decided match
+    pub fn record_match(&mut self, winner: &str, loser: &str) {
+        self.rate(winner, loser);
+        self.save();
+    }
+
+    /// @brief  The rating change on its own, whatever the winner gains the loser gives up
+    fn rate(&mut self, winner: &str, loser: &str) {
+        if winner == loser { return }
+        let (rw, rl) = (self.elo(winner), self.elo(loser));
+        let expected = 1.0 / (1.0 + 10f32.powf((rl - rw) / 400.0));
+        let change = ELO_K * (1.0 - expected);
+        for profile in self.list.iter_mut() {
+            if profile.name == winner {
+                profile.elo += change;
+            } else if profile.name == loser {
+                profile.elo -= change;
+            }
+        }
+    }
+
+    fn remove(&mut self, index: usize) {
+        if self.list.len() <= 2 { return }
+        self.list.remove(index);
+        for slot in self.selected.iter_mut() {
+            if *slot == index {
+                *slot = 0;
+            } else if *slot > index {
+                *slot -= 1;
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ProfileField {
+    PlayerOne,
+    PlayerTwo,
+    Profile,
+    Colour,
+    Remove,
+    Name,
+    Done,
+}
+
+/// The profile picker, creating, recolouring and assigning profiles to players
+pub struct ProfileScreen {
+    panel: Panel<ProfileField>,
+    /// the profile the colour and remove buttons act on
+    cursor: usize,
+}
+
+impl ProfileScreen {
+    pub fn new(profiles: &Profiles, theme: Theme) -> ProfileScreen {
+        ProfileScreen::build(profiles, 0, theme)
+    }
+
+    fn build(profiles: &Profiles, cursor: usize, theme: Theme) -> ProfileScreen {
+        let names: Vec<&str> = profiles.list.iter().map(|p| p.name.as_str()).collect();
+        let layout = Layout::vertical(4.0)
+            .with(Widget::dropdown("Player 1", ProfileField::PlayerOne, &names, profiles.selected[0]))
+            .with(Widget::dropdown("Player 2", ProfileField::PlayerTwo, &names, profiles.selected[1]))
+            .with(Widget::dropdown("Profile", ProfileField::Profile, &names, cursor))
+            .with_layout(Layout::horizontal(8.0)
+                .with(Widget::button("Colour", ProfileField::Colour))
+                .with(Widget::button("Remove", ProfileField::Remove)))
+            .with(Widget::text_input("New", ProfileField::Name, "", MAX_NAME_LEN))
+            .with(Widget::button("Done", ProfileField::Done));
+        let area = Rectangle { x: 40.0, y: 90.0, width: ARENA_WIDTH as f32 - 80.0, height: 200.0 };
+        ProfileScreen { panel: Panel::new(layout, area, theme), cursor }
+    }
+
+    /// @brief  The dropdowns hold a copy of the names, so adding or removing a
+    ///         profile lays the panel out again with focus where it was
+    fn rebuild(&mut self, profiles: &Profiles, focus: ProfileField) {
+        *self = ProfileScreen::build(profiles, self.cursor, self.panel.theme);
+        self.panel.focus_on(focus);
+    }
+
+    /// @brief  Handles input, returns true when the player backs out to the menu
+    pub unsafe fn update(&mut self, profiles: &mut Profiles, cursor: &Vector2) -> bool {
+        let capturing = self.panel.capturing();
+        match self.panel.update(cursor) {
+            Some(ProfileField::PlayerOne) => {
+                profiles.selected[0] = self.panel.get_index(ProfileField::PlayerOne).unwrap_or(0);
+            },
+            Some(ProfileField::PlayerTwo) => {
+                profiles.selected[1] = self.panel.get_index(ProfileField::PlayerTwo).unwrap_or(0);
+            },
+            Some(ProfileField::Profile) => {
+                self.cursor = self.panel.get_index(ProfileField::Profile).unwrap_or(0);
+                return false
+            },
+            Some(ProfileField::Colour) => profiles.list[self.cursor].next_color(),
+            Some(ProfileField::Remove) => {
+                profiles.remove(self.cursor);
+                self.cursor = self.cursor.min(profiles.list.len() - 1);
+                self.rebuild(profiles, ProfileField::Remove);
+            },
+            Some(ProfileField::Name) => {
+                let name = self.panel.get_text(ProfileField::Name).unwrap_or("").trim().to_string();
+                // escape lets go of the input too, but throws the name away
+                let taken = profiles.list.iter().any(|p| p.name == name);
+                if !IsKeyPressed(key!(Escape)) && !name.is_empty() && !taken {
+                    let color = PALETTE[profiles.list.len() % PALETTE.len()];
+                    profiles.list.push(Profile::new(&name, color));
+                    self.cursor = profiles.list.len() - 1;
+                }
+                self.rebuild(profiles, ProfileField::Name);
+            },
+            Some(ProfileField::Done) => return true,
+            None => return !capturing && menu_pressed(MenuKey::Back),
+        }
+        profiles.save();
+        false
+    }
+
+    pub unsafe fn draw(&self, profiles: &Profiles) {
+        let x = ARENA_WIDTH / 2;
+        draw_label("Profiles", 40, x, 50, self.panel.theme.color0);
+        // the roster under the panel, each in its own hull colour
+        for (i, profile) in profiles.list.iter().enumerate() {
+            let y = 310 + i as i32 * 24;
+            if y > ARENA_HEIGHT - 30 { break }
+            let mut text = format!("{} ({:.0})", profile.name, profile.elo);
+            if profiles.selected[0] == i { text = format!("{} [P1]", text) }
+            if profiles.selected[1] == i { text = format!("{} [P2]", text) }
+            if i == self.cursor {
+                text = format!("> {} <", text);
+            }
+            draw_label(&text, 20, x, y, profile.color);
+        }
+        // drawn last so an open dropdown covers the roster
+        self.panel.draw();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profiles(names: &[&str]) -> Profiles {
+        Profiles {
+            list: names.iter().map(|n| Profile::new(n, GREEN)).collect(),
+            selected: [0, 1],
+            path: PathBuf::new(),
+        }
+    }
+
+    fn total(profiles: &Profiles) -> f32 {
+        profiles.list.iter().map(|p| p.elo).sum()
+    }
+
+    #[test]
+    fn elo_sum_is_conserved() {
+        let mut p = profiles(&["A", "B", "C"]);
+        let before = total(&p);
+        for (winner, loser) in [("A", "B"), ("A", "C"), ("C", "B"), ("B", "A"), ("A", "B")] {
+            p.rate(winner, loser);
+            assert!((total(&p) - before).abs() < 0.01);
+        }
+    }
+
+    #[test]
+    fn even_match_moves_half_of_k() {
+        let mut p = profiles(&["A", "B"]);
+        p.rate("A", "B");
+        assert!((p.elo("A") - (START_ELO + ELO_K / 2.0)).abs() < 0.001);
+        assert!((p.elo("B") - (START_ELO - ELO_K / 2.0)).abs() < 0.001);
+    }
+
+    #[test]
+    fn upsets_move_ratings_further() {
+        let rated = || {
+            let mut p = profiles(&["Strong", "Weak"]);
+            p.list[0].elo = 1400.0;
+            p
+        };
+        let mut favourite = rated();
+        favourite.rate("Strong", "Weak");
+        let mut upset = rated();
+        upset.rate("Weak", "Strong");
+        let expected_gain = favourite.elo("Strong") - 1400.0;
+        let upset_gain = upset.elo("Weak") - START_ELO;
+        assert!(upset_gain > ELO_K / 2.0 && expected_gain < ELO_K / 2.0);
+    }
+
+    #[test]
+    fn a_profile_cannot_beat_itself() {
+        let mut p = profiles(&["A", "B"]);
+        p.rate("A", "A");
+        assert_eq!(p.elo("A"), START_ELO);
+    }
+}
@@ -0,0 +1,147 @@
+use raylib_ffi::*;
+use crate::rl::timer::Timer;
+use crate::rl::vector::vector2 as v;
+use rand::Rng;
+
+/// Furthest the view is thrown by a full strength shake, in pixels and degrees
+const MAX_SHAKE: f32 = 10.0;
+const MAX_ROLL: f32 = 2.0;
+/// How much shake wears off each second
+const SHAKE_DECAY: f32 = 1.6;
+/// Length, speed and zoom of the kill cam
+const KILL_TIME: f32 = 1.2;
+const KILL_SCALE: f32 = 0.3;
+const KILL_ZOOM: f32 = 1.6;
+/// Room auto zoom leaves around the ships it frames
+const FRAME_MARGIN: f32 = 90.0;
+/// How quickly zoom and focus ease towards where they want to be
+const EASE: f32 = 4.0;
+
+/// A `Camera2D` over the arena with screen shake, a slow motion kill cam and
+/// auto zoom, along with the time scale the simulation should run at
+pub struct CameraRig {
+    /// no shake, zoom or slow motion, for anyone those make uncomfortable
+    pub reduce_motion: bool,
+    /// zooms out as far as keeps every ship on screen, for an arena bigger than the view
+    pub auto_zoom: bool,
+    view: Vector2,
+    /// the world the view looks onto, the same size as the view for now
+    arena: Vector2,
+    trauma: f32,
+    zoom: f32,
+    focus: Vector2,
+    kill: Timer,
+    kill_focus: Vector2,
+}
+
+impl CameraRig {
+    pub fn new(width: i32, height: i32) -> CameraRig {
+        let view = Vector2 { x: width as f32, y: height as f32 };
+        CameraRig {
+            reduce_motion: false,
+            auto_zoom: false,
+            view,
+            arena: view,
+            trauma: 0.0,
+            zoom: 1.0,
+            focus: v::scale(&view, 0.5),
+            kill: Timer::new(KILL_TIME),
+            kill_focus: v::zero(),
+        }
+    }
+
+    /// @brief  Back to a still, unzoomed view of the whole arena
+    pub fn reset(&mut self) {
+        self.trauma = 0.0;
+        self.zoom = 1.0;
+        self.focus = v::scale(&self.arena, 0.5);
+        self.kill.timer = 0.0;
+    }
+
+    /// @brief  Adds to the shake, `amount` of 1 is as hard as it gets
+    pub fn shake(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).min(1.0);
+    }
+
+    /// @brief  Slows time and closes in on the killing blow for a moment
+    pub fn kill(&mut self, position: Vector2) {
+        self.kill_focus = position;
+        self.kill.start();
+    }
+
+    pub fn kill_cam(&self) -> bool {
+        self.kill.is_running()
+    }
+
+    /// @brief  How fast the game should run right now, 1 is normal speed
+    pub fn time_scale(&self) -> f32 {
+        if self.reduce_motion || !self.kill.is_running() {
+            return 1.0
+        }
+        // ease back up to full speed over the last part of the kill cam
+        let t = (self.kill.timer / KILL_TIME).clamp(0.0, 1.0);
+        1.0 + (KILL_SCALE - 1.0) * (t * 2.0).min(1.0)
+    }
+
+    /// @brief  Moves the camera on by a frame of real time, `ships` are the points auto zoom keeps in view
+    pub fn update(&mut self, ships: &[Vector2], delta: f32) {
+        self.kill.update(delta);
+        self.trauma = (self.trauma - SHAKE_DECAY * delta).max(0.0);
+        let center = v::scale(&self.arena, 0.5);
+        let (zoom, focus) = if self.reduce_motion {
+            (1.0, center)
+        } else if self.kill.is_running() {
+            (KILL_ZOOM, self.kill_focus)
+        } else if self.auto_zoom && !ships.is_empty() {
+            self.frame(ships)
+        } else {
+            (1.0, center)
+        };
+        let ease = (EASE * delta).min(1.0);
+        self.zoom += (zoom - self.zoom) * ease;
+        self.focus = v::add(&self.focus, &v::scale(&v::sub(&focus, &self.focus), ease));
+    }
+
+    /// @brief  The zoom and focus that fit a box around every ship on screen
+    /// @note   Only ever zooms out, and never past the whole arena, so while the
+    ///         arena is no bigger than the view it stays at 1 and does nothing
+    fn frame(&self, ships: &[Vector2]) -> (f32, Vector2) {
+        let (mut min, mut max) = (ships[0], ships[0]);
+        for ship in ships.iter() {
+            min = Vector2 { x: min.x.min(ship.x), y: min.y.min(ship.y) };
+            max = Vector2 { x: max.x.max(ship.x), y: max.y.max(ship.y) };
+        }
+        let size = v::add(&v::sub(&max, &min), &Vector2 { x: FRAME_MARGIN * 2.0, y: FRAME_MARGIN * 2.0 });
+        let whole = (self.view.x / self.arena.x).min(self.view.y / self.arena.y).min(1.0);
+        let zoom = (self.view.x / size.x).min(self.view.y / size.y).clamp(whole, 1.0);
+        (zoom, v::midpoint(&min, &max))
+    }
+
+    /// @brief  The raylib camera for this frame, shake included
+    /// @note   The focus is held back from the edges so a zoomed view never shows past the arena
+    pub fn camera(&self) -> Camera2D {
+        let center = v::scale(&self.view, 0.5);
+        let half = v::scale(&center, 1.0 / self.zoom);
+        let target = Vector2 {
+            x: self.focus.x.clamp(half.x, (self.arena.x - half.x).max(half.x)),
+            y: self.focus.y.clamp(half.y, (self.arena.y - half.y).max(half.y)),
+        };
+        let mut camera = Camera2D { offset: center, target, rotation: 0.0, zoom: self.zoom };
+        if !self.reduce_motion && self.trauma > 0.0 {
+            let mut rng = rand::thread_rng();
+            let strength = self.trauma * self.trauma;
+            camera.offset.x += (rng.gen::<f32>() * 2.0 - 1.0) * MAX_SHAKE * strength;
+            camera.offset.y += (rng.gen::<f32>() * 2.0 - 1.0) * MAX_SHAKE * strength;
+            camera.rotation = (rng.gen::<f32>() * 2.0 - 1.0) * MAX_ROLL * strength;
+        }
+        camera
+    }
+
+    pub unsafe fn begin(&self) {
+        BeginMode2D(self.camera());
+    }
+
+    pub unsafe fn end(&self) {
+        EndMode2D();
+    }
+}
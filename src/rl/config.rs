@@ -0,0 +1,302 @@
+use std::fmt::Display;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+#[derive(Clone, PartialEq)]
+struct Entry {
+    section: String,
+    key: String,
+    value: String,
+}
+
+/// A flat TOML subset: `[section]` headers and `key = value` lines
+/// @note   Lines that don't parse are skipped, so a damaged file loads what it can
+#[derive(Clone, Default, PartialEq)]
+pub struct Config {
+    entries: Vec<Entry>,
+}
+
+impl Config {
+    pub fn new() -> Config {
+        Config { entries: vec![] }
+    }
+
+    pub fn parse(text: &str) -> Config {
+        let mut config = Config::new();
+        let mut section = String::new();
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if line.starts_with('[') && line.ends_with(']') {
+                section = line[1..line.len() - 1].trim().to_string();
+                continue;
+            }
+            if let Some((key, value)) = line.split_once('=') {
+                let key = key.trim();
+                let value = strip_comment(value.trim());
+                if key.is_empty() || value.is_empty() {
+                    continue;
+                }
+                config.set_raw(&section, key, value.to_string());
+            }
+        }
+        config
+    }
+
+    /// @brief  Reads the file, falling back to the backup left by the last save
+    ///         when the file is missing or nothing in it could be read
+    pub fn load(path: &Path) -> Option<Config> {
+        match read(path) {
+            Some(config) if !config.entries.is_empty() => Some(config),
+            _ => Config::load_backup(path),
+        }
+    }
+
+    /// @brief  Reads only the backup, for callers that find the file itself unusable
+    pub fn load_backup(path: &Path) -> Option<Config> {
+        read(&backup_path(path))
+    }
+
+    /// @brief  Writes to a temporary file first, keeping the previous file as a backup
+    /// @note   A previous file that reads as nothing is left out, so it can't replace a good backup
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        self.write(path, true)
+    }
+
+    /// @brief  Saves without touching the backup, for when the file being replaced is known to be damaged
+    pub fn save_keeping_backup(&self, path: &Path) -> io::Result<()> {
+        self.write(path, false)
+    }
+
+    fn write(&self, path: &Path, backup: bool) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, self.serialize())?;
+        if backup && read(path).is_some_and(|config| !config.entries.is_empty()) {
+            let _ = fs::copy(path, backup_path(path));
+        }
+        fs::rename(&tmp, path)
+    }
+
+    pub fn serialize(&self) -> String {
+        let mut out = String::new();
+        for section in self.sections() {
+            if !section.is_empty() {
+                out.push_str(&format!("\n[{}]\n", section));
+            }
+            for entry in self.entries.iter().filter(|e| e.section == section) {
+                out.push_str(&format!("{} = {}\n", entry.key, entry.value));
+            }
+        }
+        out
+    }
+
+    /// @brief  Section names in the order they first appear, the top level is ""
+    pub fn sections(&self) -> Vec<String> {
+        let mut sections: Vec<String> = vec![];
+        if self.entries.iter().any(|e| e.section.is_empty()) {
+            sections.push(String::new());
+        }
+        for entry in self.entries.iter() {
+            if !sections.contains(&entry.section) {
+                sections.push(entry.section.clone());
+            }
+        }
+        sections
+    }
+
+    /// @brief  Keys set in a section, in file order
+    pub fn keys(&self, section: &str) -> Vec<String> {
+        self.entries.iter()
+            .filter(|e| e.section == section)
+            .map(|e| e.key.clone())
+            .collect()
+    }
+
+    fn get_raw(&self, section: &str, key: &str) -> Option<&str> {
+        self.entries.iter()
+            .find(|e| e.section == section && e.key == key)
+            .map(|e| e.value.as_str())
+    }
+
+    fn set_raw(&mut self, section: &str, key: &str, value: String) {
+        match self.entries.iter_mut().find(|e| e.section == section && e.key == key) {
+            Some(entry) => entry.value = value,
+            None => self.entries.push(Entry {
+                section: section.to_string(),
+                key: key.to_string(),
+                value,
+            }),
+        }
+    }
+
+    pub fn get<T: FromStr>(&self, section: &str, key: &str) -> Option<T> {
+        self.get_raw(section, key).and_then(|v| v.parse().ok())
+    }
+
+    pub fn get_or<T: FromStr>(&self, section: &str, key: &str, default: T) -> T {
+        self.get(section, key).unwrap_or(default)
+    }
+
+    pub fn get_str(&self, section: &str, key: &str) -> Option<String> {
+        let raw = self.get_raw(section, key)?;
+        if raw.len() < 2 || !raw.starts_with('"') || !raw.ends_with('"') {
+            return None
+        }
+        Some(raw[1..raw.len() - 1].replace("\\\"", "\"").replace("\\\\", "\\"))
+    }
+
+    pub fn set<T: Display>(&mut self, section: &str, key: &str, value: T) {
+        self.set_raw(section, key, value.to_string());
+    }
+
+    pub fn set_str(&mut self, section: &str, key: &str, value: &str) {
+        let escaped = value.replace('\\', "\\\\").replace('"', "\\\"");
+        self.set_raw(section, key, format!("\"{}\"", escaped));
+    }
+
+    pub fn remove(&mut self, section: &str, key: &str) {
+        self.entries.retain(|e| e.section != section || e.key != key);
+    }
+}
+
+fn read(path: &Path) -> Option<Config> {
+    fs::read_to_string(path).ok().map(|text| Config::parse(&text))
+}
+
+fn backup_path(path: &Path) -> PathBuf {
+    path.with_extension("bak")
+}
+
+/// @brief  Drops a trailing `# comment`, leaving any `#` inside quotes alone
+fn strip_comment(value: &str) -> &str {
+    let mut quoted = false;
+    let mut escaped = false;
+    for (i, c) in value.char_indices() {
+        match c {
+            '\\' if quoted => { escaped = !escaped; continue },
+            '"' if !escaped => quoted = !quoted,
+            '#' if !quoted => return value[..i].trim(),
+            _ => {},
+        }
+        escaped = false;
+    }
+    value
+}
+
+/// A temporary directory for tests that touch files, removed again when dropped
+#[cfg(test)]
+pub struct Scratch {
+    dir: PathBuf,
+}
+
+#[cfg(test)]
+impl Scratch {
+    pub fn new(name: &str) -> Scratch {
+        let dir = std::env::temp_dir().join(format!("spacewar-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        Scratch { dir }
+    }
+
+    pub fn file(&self, name: &str) -> PathBuf {
+        self.dir.join(name)
+    }
+}
+
+#[cfg(test)]
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_reads_sections_and_keys() {
+        let config = Config::parse("version = 1\n\n[profile.0]\nname = \"Ada\"\nshots = 12\n");
+        assert_eq!(config.sections(), vec!["".to_string(), "profile.0".to_string()]);
+        assert_eq!(config.get::<u32>("", "version"), Some(1));
+        assert_eq!(config.get_str("profile.0", "name"), Some("Ada".to_string()));
+        assert_eq!(config.get::<u32>("profile.0", "shots"), Some(12));
+        assert_eq!(config.get::<u32>("", "shots"), None);
+    }
+
+    #[test]
+    fn parse_skips_comments_and_broken_lines() {
+        let config = Config::parse("# header\n[open\n= 3\nkey =\nnoise\ngood = 2 # trailing\n");
+        assert_eq!(config.keys(""), vec!["good".to_string()]);
+        assert_eq!(config.get::<u32>("", "good"), Some(2));
+    }
+
+    #[test]
+    fn parse_keeps_the_last_of_a_repeated_key() {
+        let config = Config::parse("a = 1\na = 2\n");
+        assert_eq!(config.get::<u32>("", "a"), Some(2));
+        assert_eq!(config.keys("").len(), 1);
+    }
+
+    #[test]
+    fn strip_comment_leaves_quoted_hashes() {
+        assert_eq!(strip_comment("3 # three"), "3");
+        assert_eq!(strip_comment("\"#1\""), "\"#1\"");
+        assert_eq!(strip_comment("\"a \\\" # b\" # c"), "\"a \\\" # b\"");
+        assert_eq!(strip_comment("# all comment"), "");
+        assert_eq!(strip_comment("plain"), "plain");
+    }
+
+    #[test]
+    fn serialize_round_trips() {
+        let mut config = Config::new();
+        config.set("", "version", 1);
+        config.set_str("profile.0", "name", "say \"hi\" # now");
+        let back = Config::parse(&config.serialize());
+        assert_eq!(back.get::<u32>("", "version"), Some(1));
+        assert_eq!(back.get_str("profile.0", "name"), Some("say \"hi\" # now".to_string()));
+    }
+
+    #[test]
+    fn load_falls_back_to_the_backup() {
+        let scratch = Scratch::new("config-fallback");
+        let path = scratch.file("test.toml");
+        fs::write(&path, "not toml at all\n").unwrap();
+        fs::write(backup_path(&path), "kept = 1\n").unwrap();
+        assert_eq!(Config::load(&path).and_then(|c| c.get::<u32>("", "kept")), Some(1));
+        fs::remove_file(&path).unwrap();
+        assert_eq!(Config::load(&path).and_then(|c| c.get::<u32>("", "kept")), Some(1));
+    }
+
+    #[test]
+    fn save_never_backs_up_an_unreadable_file() {
+        let scratch = Scratch::new("config-backup");
+        let path = scratch.file("test.toml");
+        fs::write(&path, "garbage\n").unwrap();
+        fs::write(backup_path(&path), "kept = 1\n").unwrap();
+        let mut config = Config::new();
+        config.set("", "fresh", 2);
+        config.save(&path).unwrap();
+        assert_eq!(fs::read_to_string(backup_path(&path)).unwrap(), "kept = 1\n");
+        // a readable file does become the backup on the next save
+        config.save(&path).unwrap();
+        assert_eq!(Config::load_backup(&path).and_then(|c| c.get::<u32>("", "fresh")), Some(2));
+    }
+
+    #[test]
+    fn save_keeping_backup_leaves_it_alone() {
+        let scratch = Scratch::new("config-keep");
+        let path = scratch.file("test.toml");
+        fs::write(&path, "damaged = 1\n").unwrap();
+        fs::write(backup_path(&path), "kept = 1\n").unwrap();
+        Config::new().save_keeping_backup(&path).unwrap();
+        assert_eq!(fs::read_to_string(backup_path(&path)).unwrap(), "kept = 1\n");
+    }
+}
@@ -1,8 +1,12 @@
 use raylib_ffi::*;
 use colors::*;
 use crate::delta;
+use crate::{key, mouse_b};
+use crate::rl::input::{menu_pressed, MenuKey};
 use crate::rl::vector::vector2 as v;
+use crate::rl::view::{ARENA_WIDTH, ARENA_HEIGHT};
 
+#[derive(Clone, Copy)]
 pub enum TextPosition {
     TL,
     TR,
@@ -16,9 +20,9 @@ pub unsafe fn draw_text(text_s: String, pos: TextPosition, y_off: i32, font_size
     let padding = 8;
     match pos {
         TextPosition::TL => DrawText(rl_str!(text), padding, font_size * y_off + padding, font_size, color),
-        TextPosition::BL => DrawText(rl_str!(text), padding, GetScreenHeight() - font_size * y_off - padding, font_size, color),
-        TextPosition::BR => DrawText(rl_str!(text), GetScreenWidth() - padding - text_w, GetScreenHeight() - font_size * y_off - padding, font_size, color),
-        TextPosition::TR => DrawText(rl_str!(text), GetScreenWidth() - padding - text_w, font_size * y_off + padding, font_size, color),
+        TextPosition::BL => DrawText(rl_str!(text), padding, ARENA_HEIGHT - font_size * y_off - padding, font_size, color),
+        TextPosition::BR => DrawText(rl_str!(text), ARENA_WIDTH - padding - text_w, ARENA_HEIGHT - font_size * y_off - padding, font_size, color),
+        TextPosition::TR => DrawText(rl_str!(text), ARENA_WIDTH - padding - text_w, font_size * y_off + padding, font_size, color),
     }
 }
 
@@ -45,24 +49,49 @@ pub unsafe fn draw_label(text: &str, font_size: i32, x: i32, y: i32, color: Colo
     DrawText(rl_str!(text), x - width / 2, y - height / 2, font_size, color); 
 }
 
-pub struct Button {
+/// @brief  Draws a horizontal bar filled to the given fraction
+pub unsafe fn draw_bar(rec: Rectangle, fill: f32, color0: Color, color1: Color) {
+    DrawRectangleRec(rec, color1);
+    DrawRectangleRec(Rectangle { width: rec.width * fill.clamp(0.0, 1.0), ..rec }, color0);
+    DrawRectangleLinesEx(rec, 1.0, color0);
+}
+
+/// @brief  Appends the characters typed this frame to the text, backspace removes the last one
+pub unsafe fn edit_text(text: &mut String, max_len: usize) {
+    let mut code = GetCharPressed();
+    while code > 0 {
+        if let Some(c) = char::from_u32(code as u32) {
+            if !c.is_control() && text.chars().count() < max_len {
+                text.push(c);
+            }
+        }
+        code = GetCharPressed();
+    }
+    if IsKeyPressed(key!(Backspace)) || IsKeyPressedRepeat(key!(Backspace)) {
+        text.pop();
+    }
+}
+
+/// A clickable label that hands back its action when activated
+pub struct Button<A> {
     x: f32,
     y: f32,
     font_size: i32,
     color0: Color,
     color1: Color,
     pub label: String, 
+    pub action: A,
     selected: bool,
 }
 
-impl Button {
-    pub fn new(label: String, x: f32, y: f32, font_size: i32, color0: Color, color1: Color) -> Button {
+impl<A: Copy> Button<A> {
+    pub fn new(label: String, action: A, x: f32, y: f32, font_size: i32, color0: Color, color1: Color) -> Button<A> {
         Button {
-            x, y, font_size, color0, color1, label, selected: false, 
+            x, y, font_size, color0, color1, label, action, selected: false, 
         }
     }
 
-    pub unsafe fn new_center(label: &String, center: &Vector2, font_size: i32, color0: Color, color1: Color) -> Button {
+    pub unsafe fn new_center(label: &String, action: A, center: &Vector2, font_size: i32, color0: Color, color1: Color) -> Button<A> {
         Button {
             x: center.x - measure_text_w(label, font_size) / 2.0,
             y: center.y - measure_text_h(label, font_size) / 2.0,
@@ -70,22 +99,24 @@ impl Button {
             color0,
             color1,
             label: label.to_string(),
+            action,
             selected: false,
         }
     }
 
     /// @brief  Creates a row of buttons at the given row position
     /// @note   Screen Percent is the percent from the screen (bottom up)
-    pub unsafe fn new_row(screen_percent: f32, labels: Vec<String>, font_size: i32, min: f32, max: f32, color0: Color, color1: Color) -> Vec<Button> {
-        let mut buttons: Vec<Button> = vec![];
-        let pos_off = (max - min) / (labels.len() + 1) as f32;
-        for i in 0..labels.len() {
+    pub unsafe fn new_row(screen_percent: f32, items: Vec<(String, A)>, font_size: i32, min: f32, max: f32, color0: Color, color1: Color) -> Vec<Button<A>> {
+        let mut buttons: Vec<Button<A>> = vec![];
+        let pos_off = (max - min) / (items.len() + 1) as f32;
+        for (i, (label, action)) in items.iter().enumerate() {
             buttons.push(
                 Button::new_center(
-                    &labels[i],
+                    label,
+                    *action,
                     &Vector2 {
                         x: pos_off * (i + 1) as f32 + min,
-                        y: GetScreenHeight() as f32 - GetScreenHeight() as f32 * screen_percent
+                        y: ARENA_HEIGHT as f32 - ARENA_HEIGHT as f32 * screen_percent
                     },
                     font_size, 
                     color0,
@@ -96,17 +127,18 @@ impl Button {
         buttons
     }
 
-    pub unsafe fn new_list_centered(y_off: i32, padding: i32, font_size: i32, min: f32, max: f32, color0: Color, color1: Color, labels: Vec<String>) -> Vec<Button> {
-        let mut buttons: Vec<Button> = vec![];
+    pub unsafe fn new_list_centered(y_off: i32, padding: i32, font_size: i32, min: f32, max: f32, color0: Color, color1: Color, items: Vec<(String, A)>) -> Vec<Button<A>> {
+        let mut buttons: Vec<Button<A>> = vec![];
         let pad = padding as f32;
         let x = (max - min) / 2.0;
-        for i in 0..labels.len() {
+        for (i, (label, action)) in items.iter().enumerate() {
             buttons.push(
                 Button::new_center(
-                    &labels[i],
+                    label,
+                    *action,
                     &Vector2 {
                         x, 
-                        y: (GetScreenHeight() / 2 + y_off) as f32 + (measure_text_h(&labels[i], font_size) + pad) * i as f32
+                        y: (ARENA_HEIGHT / 2 + y_off) as f32 + (measure_text_h(label, font_size) + pad) * i as f32
                     },
                     font_size,
                     color0,
@@ -146,8 +178,11 @@ impl Button {
         measure_text_h(&self.label, self.font_size) + 5.0
     }
 
-    pub unsafe fn draw(&self, cursor: &Vector2) {
-        if v::within(&cursor, &self.rec()) || self.selected {
+    /// @brief  Draws the button, highlighted while selected
+    /// @note   Hovering only highlights through the selection, so the caller
+    ///         keeps one highlighted button whether it follows keys or the mouse
+    pub unsafe fn draw(&self) {
+        if self.selected {
             DrawRectangleRec(self.rec(), self.color1);
             DrawRectangleLinesEx(self.rec(), 2.0, self.color0);
             DrawText(rl_str!(&self.label), self.x as i32 + 5, self.y as i32 + 5, self.font_size, self.color0); 
@@ -157,3 +192,620 @@ impl Button {
         DrawText(rl_str!(&self.label), self.x as i32 + 5, self.y as i32 + 5, self.font_size, self.color1); 
     }
 }
+
+/// A list of buttons with one selection shared by the keyboard, gamepad and mouse
+pub struct Menu<A> {
+    pub buttons: Vec<Button<A>>,
+    selected: Option<usize>,
+}
+
+impl<A: Copy> Menu<A> {
+    pub fn new(buttons: Vec<Button<A>>) -> Menu<A> {
+        Menu { buttons, selected: None }
+    }
+
+    pub fn selected(&self) -> Option<A> {
+        self.selected.map(|i| self.buttons[i].action)
+    }
+
+    pub fn select(&mut self, selected: Option<usize>) {
+        self.selected = selected;
+        for (i, button) in self.buttons.iter_mut().enumerate() {
+            button.select(Some(i) == selected);
+        }
+    }
+
+    /// @brief  Moves the selection, wrapping around at either end
+    fn step(&mut self, forward: bool) {
+        let count = self.buttons.len();
+        if count == 0 { return }
+        let next = match (self.selected, forward) {
+            (None, true) => 0,
+            (None, false) => count - 1,
+            (Some(i), true) => (i + 1) % count,
+            (Some(i), false) => (i + count - 1) % count,
+        };
+        self.select(Some(next));
+    }
+
+    /// @brief  Handles a frame of navigation, returns the action of an activated button
+    /// @note   The pointer only takes over the selection when it moves, so it
+    ///         doesn't fight the keyboard while resting on a button
+    pub unsafe fn update(&mut self, cursor: &Vector2) -> Option<A> {
+        let hovered = self.buttons.iter().position(|b| b.within(cursor));
+        if hovered.is_some() && !v::is_zero(&GetMouseDelta()) {
+            self.select(hovered);
+        }
+        let wheel = GetMouseWheelMove();
+        if menu_pressed(MenuKey::Up) || wheel > 0.0 {
+            self.step(false);
+        }
+        if menu_pressed(MenuKey::Down) || wheel < 0.0 {
+            self.step(true);
+        }
+        if menu_pressed(MenuKey::Back) {
+            self.select(None);
+        }
+        if IsMouseButtonPressed(mouse_b!(Left)) && hovered.is_some() {
+            self.select(hovered);
+            return self.selected()
+        }
+        if menu_pressed(MenuKey::Accept) {
+            return self.selected()
+        }
+        None
+    }
+
+    pub unsafe fn draw(&self) {
+        self.buttons.iter().for_each(|b| b.draw());
+    }
+}
+
+/// Colours every widget is drawn with, `color0` on `color1` like `Button`
+#[derive(Clone, Copy)]
+pub struct Theme {
+    pub name: &'static str,
+    pub color0: Color,
+    pub color1: Color,
+    pub accent: Color,
+    pub muted: Color,
+    pub font_size: i32,
+}
+
+impl Theme {
+    pub const CLASSIC: Theme = Theme { name: "Classic", color0: WHITE, color1: BLACK, accent: YELLOW, muted: GRAY, font_size: 20 };
+    /// the green of the PDP-1's type 30 display
+    pub const PHOSPHOR: Theme = Theme {
+        name: "Phosphor",
+        color0: Color { r: 90, g: 255, b: 120, a: 255 },
+        color1: Color { r: 0, g: 16, b: 4, a: 255 },
+        accent: Color { r: 200, g: 255, b: 210, a: 255 },
+        muted: Color { r: 30, g: 120, b: 50, a: 255 },
+        font_size: 20,
+    };
+    pub const AMBER: Theme = Theme {
+        name: "Amber",
+        color0: Color { r: 255, g: 176, b: 0, a: 255 },
+        color1: Color { r: 20, g: 10, b: 0, a: 255 },
+        accent: Color { r: 255, g: 230, b: 160, a: 255 },
+        muted: Color { r: 140, g: 90, b: 0, a: 255 },
+        font_size: 20,
+    };
+    pub const ICE: Theme = Theme {
+        name: "Ice",
+        color0: SKYBLUE,
+        color1: Color { r: 4, g: 10, b: 24, a: 255 },
+        accent: WHITE,
+        muted: Color { r: 60, g: 90, b: 130, a: 255 },
+        font_size: 20,
+    };
+    pub const CRIMSON: Theme = Theme {
+        name: "Crimson",
+        color0: Color { r: 255, g: 96, b: 96, a: 255 },
+        color1: Color { r: 24, g: 0, b: 0, a: 255 },
+        accent: WHITE,
+        muted: Color { r: 130, g: 40, b: 40, a: 255 },
+        font_size: 20,
+    };
+
+    /// Every theme offered on the settings screen, the first is the default
+    pub const ALL: [Theme; 5] = [Theme::CLASSIC, Theme::PHOSPHOR, Theme::AMBER, Theme::ICE, Theme::CRIMSON];
+
+    /// @brief  The theme with the given name, an unknown one falls back to classic
+    pub fn named(name: &str) -> Theme {
+        Theme::ALL.iter().find(|t| t.name.eq_ignore_ascii_case(name)).copied().unwrap_or(Theme::CLASSIC)
+    }
+
+    fn row_height(&self) -> f32 {
+        (self.font_size + 10) as f32
+    }
+}
+
+pub enum Control {
+    Label,
+    Button,
+    Toggle(bool),
+    Slider { value: f32, min: f32, max: f32, step: f32 },
+    Dropdown { options: Vec<String>, index: usize, open: bool },
+    Stepper { value: i32, min: i32, max: i32 },
+    TextInput { text: String, max_len: usize, editing: bool },
+}
+
+/// A single row of a `Panel`, the label on the left and its control on the right
+pub struct Widget<A> {
+    pub label: String,
+    pub action: Option<A>,
+    pub control: Control,
+    rec: Rectangle,
+}
+
+impl<A: Copy + PartialEq> Widget<A> {
+    fn with(label: &str, action: Option<A>, control: Control) -> Widget<A> {
+        Widget {
+            label: label.to_string(),
+            action,
+            control,
+            rec: Rectangle { x: 0.0, y: 0.0, width: 0.0, height: 0.0 },
+        }
+    }
+
+    pub fn label(text: &str) -> Widget<A> {
+        Widget::with(text, None, Control::Label)
+    }
+
+    pub fn button(label: &str, action: A) -> Widget<A> {
+        Widget::with(label, Some(action), Control::Button)
+    }
+
+    pub fn toggle(label: &str, action: A, on: bool) -> Widget<A> {
+        Widget::with(label, Some(action), Control::Toggle(on))
+    }
+
+    pub fn slider(label: &str, action: A, value: f32, min: f32, max: f32, step: f32) -> Widget<A> {
+        Widget::with(label, Some(action), Control::Slider { value: value.clamp(min, max), min, max, step })
+    }
+
+    pub fn dropdown(label: &str, action: A, options: &[&str], index: usize) -> Widget<A> {
+        let options: Vec<String> = options.iter().map(|o| o.to_string()).collect();
+        let index = index.min(options.len().saturating_sub(1));
+        Widget::with(label, Some(action), Control::Dropdown { options, index, open: false })
+    }
+
+    pub fn stepper(label: &str, action: A, value: i32, min: i32, max: i32) -> Widget<A> {
+        Widget::with(label, Some(action), Control::Stepper { value: value.clamp(min, max), min, max })
+    }
+
+    pub fn text_input(label: &str, action: A, text: &str, max_len: usize) -> Widget<A> {
+        Widget::with(label, Some(action), Control::TextInput { text: text.to_string(), max_len, editing: false })
+    }
+
+    fn focusable(&self) -> bool {
+        !matches!(self.control, Control::Label)
+    }
+
+    /// @brief  Whether the widget is holding on to input, typing or picking an option
+    fn capturing(&self) -> bool {
+        matches!(self.control, Control::TextInput { editing: true, .. } | Control::Dropdown { open: true, .. })
+    }
+
+    /// @brief  The right half of the row, where values and bars go
+    fn value_rec(&self) -> Rectangle {
+        Rectangle { x: self.rec.x + self.rec.width / 2.0, width: self.rec.width / 2.0, ..self.rec }
+    }
+
+    fn option_rec(&self, i: usize) -> Rectangle {
+        Rectangle { y: self.rec.y + self.rec.height * (i + 1) as f32, ..self.value_rec() }
+    }
+
+    /// @brief  Nudges the value left or right, returns true if it changed
+    fn adjust(&mut self, direction: i32) -> bool {
+        match &mut self.control {
+            Control::Toggle(on) => {
+                *on = !*on;
+                true
+            },
+            Control::Slider { value, min, max, step } => {
+                let next = (*value + *step * direction as f32).clamp(*min, *max);
+                let changed = next != *value;
+                *value = next;
+                changed
+            },
+            Control::Dropdown { options, index, .. } => {
+                let count = options.len();
+                if count == 0 { return false }
+                *index = (*index as i32 + direction).rem_euclid(count as i32) as usize;
+                true
+            },
+            Control::Stepper { value, min, max } => {
+                let next = (*value + direction).clamp(*min, *max);
+                let changed = next != *value;
+                *value = next;
+                changed
+            },
+            _ => false,
+        }
+    }
+
+    /// @brief  Enter on a focused widget, returns true when its action should fire
+    fn activate(&mut self) -> bool {
+        match &mut self.control {
+            Control::Button => true,
+            Control::Toggle(_) => self.adjust(1),
+            Control::Dropdown { open, .. } => {
+                *open = !*open;
+                !*open
+            },
+            Control::TextInput { editing, .. } => {
+                *editing = !*editing;
+                !*editing
+            },
+            _ => false,
+        }
+    }
+
+    /// @brief  A click at the cursor, returns true when its action should fire
+    unsafe fn click(&mut self, cursor: &Vector2) -> bool {
+        let picked = match &self.control {
+            Control::Dropdown { open: true, options, .. } => {
+                (0..options.len()).find(|i| v::within(cursor, &self.option_rec(*i)))
+            },
+            _ => None,
+        };
+        if let (Some(i), Control::Dropdown { index, open, .. }) = (picked, &mut self.control) {
+            *index = i;
+            *open = false;
+            return true
+        }
+        let value_rec = self.value_rec();
+        match self.control {
+            Control::Slider { .. } => self.drag(cursor),
+            Control::Stepper { .. } => {
+                let direction = if cursor.x < value_rec.x + value_rec.width / 2.0 { -1 } else { 1 };
+                self.adjust(direction)
+            },
+            _ => self.activate(),
+        }
+    }
+
+    /// @brief  Sets a slider from where the cursor sits along its bar
+    unsafe fn drag(&mut self, cursor: &Vector2) -> bool {
+        let bar = self.value_rec();
+        if let Control::Slider { value, min, max, step } = &mut self.control {
+            let t = ((cursor.x - bar.x) / bar.width).clamp(0.0, 1.0);
+            let mut next = *min + (*max - *min) * t;
+            if *step > 0.0 {
+                next = (*min + ((next - *min) / *step).round() * *step).clamp(*min, *max);
+            }
+            let changed = next != *value;
+            *value = next;
+            return changed
+        }
+        false
+    }
+
+    unsafe fn draw(&self, theme: &Theme, focused: bool) {
+        let font = theme.font_size;
+        let text_y = (self.rec.y + (self.rec.height - font as f32) / 2.0) as i32;
+        let fg = if focused { theme.accent } else { theme.color0 };
+        if focused {
+            DrawRectangleLinesEx(self.rec, 1.0, theme.muted);
+        }
+        let value_rec = self.value_rec();
+        let value_x = value_rec.x as i32 + 5;
+        match &self.control {
+            Control::Label => {
+                DrawText(rl_str!(&self.label), self.rec.x as i32 + 5, text_y, font, theme.muted);
+                return
+            },
+            Control::Button => {
+                let (bg, text) = if focused { (theme.color1, theme.color0) } else { (theme.color0, theme.color1) };
+                DrawRectangleRec(self.rec, bg);
+                DrawRectangleLinesEx(self.rec, 2.0, theme.color0);
+                let width = MeasureText(rl_str!(&self.label), font);
+                DrawText(rl_str!(&self.label), (self.rec.x + self.rec.width / 2.0) as i32 - width / 2, text_y, font, text);
+                return
+            },
+            _ => {},
+        }
+        DrawText(rl_str!(&self.label), self.rec.x as i32 + 5, text_y, font, fg);
+        match &self.control {
+            Control::Toggle(on) => {
+                let text = if *on { "[On]" } else { "[Off]" };
+                DrawText(rl_str!(text), value_x, text_y, font, fg);
+            },
+            Control::Slider { value, min, max, .. } => {
+                let bar = Rectangle { y: value_rec.y + value_rec.height / 2.0 - 4.0, height: 8.0, ..value_rec };
+                let fill = if max > min { (value - min) / (max - min) } else { 0.0 };
+                draw_bar(bar, fill, fg, theme.color1);
+            },
+            Control::Dropdown { options, index, .. } => {
+                let text = format!("< {} >", options.get(*index).map_or("", |o| o.as_str()));
+                DrawText(rl_str!(&text), value_x, text_y, font, fg);
+            },
+            Control::Stepper { value, .. } => {
+                let text = format!("< {} >", value);
+                DrawText(rl_str!(&text), value_x, text_y, font, fg);
+            },
+            Control::TextInput { text, editing, .. } => {
+                DrawRectangleLinesEx(value_rec, 1.0, fg);
+                let caret = if *editing && (GetTime() * 2.0) as i32 % 2 == 0 { "_" } else { "" };
+                let text = format!("{}{}", text, caret);
+                DrawText(rl_str!(&text), value_x, text_y, font, fg);
+            },
+            _ => {},
+        }
+    }
+
+    /// @brief  An open dropdown's options, drawn last so they sit over the rows below
+    unsafe fn draw_options(&self, theme: &Theme) {
+        if let Control::Dropdown { options, index, open: true } = &self.control {
+            for (i, option) in options.iter().enumerate() {
+                let rec = self.option_rec(i);
+                let (bg, fg) = if i == *index { (theme.color0, theme.color1) } else { (theme.color1, theme.color0) };
+                DrawRectangleRec(rec, bg);
+                DrawRectangleLinesEx(rec, 1.0, theme.color0);
+                let text_y = (rec.y + (rec.height - theme.font_size as f32) / 2.0) as i32;
+                DrawText(rl_str!(option), rec.x as i32 + 5, text_y, theme.font_size, fg);
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Direction {
+    Vertical,
+    Horizontal,
+}
+
+pub enum Node<A> {
+    Widget(Widget<A>),
+    Layout(Layout<A>),
+}
+
+/// Stacks widgets and nested layouts down the page or across it
+pub struct Layout<A> {
+    pub direction: Direction,
+    pub spacing: f32,
+    children: Vec<Node<A>>,
+}
+
+impl<A: Copy + PartialEq> Layout<A> {
+    pub fn vertical(spacing: f32) -> Layout<A> {
+        Layout { direction: Direction::Vertical, spacing, children: vec![] }
+    }
+
+    pub fn horizontal(spacing: f32) -> Layout<A> {
+        Layout { direction: Direction::Horizontal, spacing, children: vec![] }
+    }
+
+    pub fn with(mut self, widget: Widget<A>) -> Layout<A> {
+        self.children.push(Node::Widget(widget));
+        self
+    }
+
+    pub fn with_layout(mut self, layout: Layout<A>) -> Layout<A> {
+        self.children.push(Node::Layout(layout));
+        self
+    }
+
+    /// @brief  Places every child inside the given width, returns the height used
+    fn arrange(&mut self, x: f32, y: f32, width: f32, row_height: f32) -> f32 {
+        let count = self.children.len().max(1) as f32;
+        let column = (width - self.spacing * (count - 1.0)) / count;
+        let mut used: f32 = 0.0;
+        for (i, child) in self.children.iter_mut().enumerate() {
+            let (cx, cy, cw) = match self.direction {
+                Direction::Vertical => (x, y + used + if i > 0 { self.spacing } else { 0.0 }, width),
+                Direction::Horizontal => (x + (column + self.spacing) * i as f32, y, column),
+            };
+            let height = match child {
+                Node::Widget(widget) => {
+                    widget.rec = Rectangle { x: cx, y: cy, width: cw, height: row_height };
+                    row_height
+                },
+                Node::Layout(layout) => layout.arrange(cx, cy, cw, row_height),
+            };
+            used = match self.direction {
+                Direction::Vertical => cy + height - y,
+                Direction::Horizontal => used.max(height),
+            };
+        }
+        used
+    }
+
+    fn widgets<'a>(&'a self, out: &mut Vec<&'a Widget<A>>) {
+        for child in self.children.iter() {
+            match child {
+                Node::Widget(widget) => out.push(widget),
+                Node::Layout(layout) => layout.widgets(out),
+            }
+        }
+    }
+
+    fn widgets_mut<'a>(&'a mut self, out: &mut Vec<&'a mut Widget<A>>) {
+        for child in self.children.iter_mut() {
+            match child {
+                Node::Widget(widget) => out.push(widget),
+                Node::Layout(layout) => layout.widgets_mut(out),
+            }
+        }
+    }
+}
+
+/// A laid out tree of widgets with one focused at a time, reachable from the
+/// keyboard, a gamepad or the mouse
+pub struct Panel<A> {
+    pub theme: Theme,
+    root: Layout<A>,
+    focus: Option<usize>,
+}
+
+impl<A: Copy + PartialEq> Panel<A> {
+    pub fn new(mut root: Layout<A>, rec: Rectangle, theme: Theme) -> Panel<A> {
+        root.arrange(rec.x, rec.y, rec.width, theme.row_height());
+        let mut panel = Panel { theme, root, focus: None };
+        panel.focus = panel.widgets().iter().position(|w| w.focusable());
+        panel
+    }
+
+    fn widgets(&self) -> Vec<&Widget<A>> {
+        let mut out = vec![];
+        self.root.widgets(&mut out);
+        out
+    }
+
+    fn widgets_mut(&mut self) -> Vec<&mut Widget<A>> {
+        let mut out = vec![];
+        self.root.widgets_mut(&mut out);
+        out
+    }
+
+    fn find(&self, action: A) -> Option<&Widget<A>> {
+        self.widgets().into_iter().find(|w| w.action == Some(action))
+    }
+
+    /// @brief  True while a text input or dropdown is taking every key, so the
+    ///         caller shouldn't treat Escape as leaving the screen
+    pub fn capturing(&self) -> bool {
+        self.widgets().iter().any(|w| w.capturing())
+    }
+
+    /// @brief  Moves focus to the widget with the given action, so a rebuilt panel
+    ///         can pick up where the old one left off
+    pub fn focus_on(&mut self, action: A) {
+        if let Some(i) = self.widgets().iter().position(|w| w.action == Some(action)) {
+            self.focus = Some(i);
+        }
+    }
+
+    /// @brief  Moves focus to the next focusable widget, wrapping around
+    fn step(&mut self, forward: bool) {
+        let focusable: Vec<bool> = self.widgets().iter().map(|w| w.focusable()).collect();
+        let count = focusable.len();
+        if !focusable.contains(&true) { return }
+        let mut i = self.focus.unwrap_or(if forward { count - 1 } else { 0 });
+        loop {
+            i = if forward { (i + 1) % count } else { (i + count - 1) % count };
+            if focusable[i] { break }
+        }
+        self.focus = Some(i);
+    }
+
+    /// @brief  Handles a frame of input, returns the action of a widget that was
+    ///         pressed or changed so the caller can read its new value
+    pub unsafe fn update(&mut self, cursor: &Vector2) -> Option<A> {
+        let focus = self.focus;
+        let widgets = self.widgets_mut();
+        let hovered = widgets.iter().position(|w| w.focusable() && v::within(cursor, &w.rec));
+
+        // a capturing widget gets every key until it lets go
+        if let Some(widget) = focus.and_then(|i| widgets.into_iter().nth(i)).filter(|w| w.capturing()) {
+            match &mut widget.control {
+                Control::TextInput { text, max_len, editing } => {
+                    edit_text(text, *max_len);
+                    if IsKeyPressed(key!(Enter)) || IsKeyPressed(key!(Escape)) {
+                        *editing = false;
+                        return widget.action
+                    }
+                },
+                Control::Dropdown { .. } => {
+                    if menu_pressed(MenuKey::Up) { widget.adjust(-1); }
+                    if menu_pressed(MenuKey::Down) { widget.adjust(1); }
+                    if IsMouseButtonPressed(mouse_b!(Left)) {
+                        if widget.click(cursor) {
+                            return widget.action
+                        }
+                    } else if menu_pressed(MenuKey::Accept) || menu_pressed(MenuKey::Back) {
+                        widget.activate();
+                        return widget.action
+                    }
+                },
+                _ => {},
+            }
+            return None
+        }
+
+        if hovered.is_some() && !v::is_zero(&GetMouseDelta()) {
+            self.focus = hovered;
+        }
+        if menu_pressed(MenuKey::Up) {
+            self.step(false);
+        }
+        if menu_pressed(MenuKey::Down) {
+            self.step(true);
+        }
+        let clicked = IsMouseButtonPressed(mouse_b!(Left));
+        if clicked && hovered.is_some() {
+            self.focus = hovered;
+        }
+        let focus = self.focus;
+        let mut widgets = self.widgets_mut();
+        if let Some(i) = hovered {
+            let widget = &mut widgets[i];
+            if clicked {
+                return if widget.click(cursor) { widget.action } else { None }
+            }
+            let dragging = IsMouseButtonDown(mouse_b!(Left)) && matches!(widget.control, Control::Slider { .. });
+            if dragging && widget.drag(cursor) {
+                return widget.action
+            }
+        }
+        let widget = match focus {
+            Some(i) => &mut widgets[i],
+            None => return None,
+        };
+        let changed = if menu_pressed(MenuKey::Left) {
+            widget.adjust(-1)
+        } else if menu_pressed(MenuKey::Right) {
+            widget.adjust(1)
+        } else if menu_pressed(MenuKey::Accept) {
+            widget.activate()
+        } else {
+            false
+        };
+        if changed { widget.action } else { None }
+    }
+
+    pub unsafe fn draw(&self) {
+        let widgets = self.widgets();
+        for (i, widget) in widgets.iter().enumerate() {
+            widget.draw(&self.theme, self.focus == Some(i));
+        }
+        widgets.iter().for_each(|w| w.draw_options(&self.theme));
+    }
+
+    pub fn get_bool(&self, action: A) -> Option<bool> {
+        match self.find(action)?.control {
+            Control::Toggle(on) => Some(on),
+            _ => None,
+        }
+    }
+
+    pub fn get_f32(&self, action: A) -> Option<f32> {
+        match self.find(action)?.control {
+            Control::Slider { value, .. } => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn get_i32(&self, action: A) -> Option<i32> {
+        match self.find(action)?.control {
+            Control::Stepper { value, .. } => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn get_index(&self, action: A) -> Option<usize> {
+        match self.find(action)?.control {
+            Control::Dropdown { index, .. } => Some(index),
+            _ => None,
+        }
+    }
+
+    pub fn get_text(&self, action: A) -> Option<&str> {
+        match &self.find(action)?.control {
+            Control::TextInput { text, .. } => Some(text),
+            _ => None,
+        }
+    }
+}
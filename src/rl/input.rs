@@ -0,0 +1,442 @@
+use raylib_ffi::*;
+use enums::{GamepadAxis, GamepadButton, KeyboardKey};
+use crate::rl::config::Config;
+use std::ffi::CStr;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Action {
+    TurnLeft,
+    TurnRight,
+    Thrust,
+    Reverse,
+    Fire,
+    ToggleView,
+    SwitchWeapon,
+    Hyperspace,
+    Trajectory,
+    Pause,
+}
+
+impl Action {
+    pub const ALL: [Action; 10] = [
+        Action::TurnLeft,
+        Action::TurnRight,
+        Action::Thrust,
+        Action::Reverse,
+        Action::Fire,
+        Action::ToggleView,
+        Action::SwitchWeapon,
+        Action::Hyperspace,
+        Action::Trajectory,
+        Action::Pause,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::TurnLeft => "Turn Left",
+            Action::TurnRight => "Turn Right",
+            Action::Thrust => "Thrust",
+            Action::Reverse => "Reverse",
+            Action::Fire => "Fire",
+            Action::ToggleView => "Toggle View",
+            Action::SwitchWeapon => "Switch Weapon",
+            Action::Hyperspace => "Hyperspace",
+            Action::Trajectory => "Trajectory",
+            Action::Pause => "Pause",
+        }
+    }
+
+    /// @brief  The key this action is stored under in the config file
+    fn config_key(&self) -> &'static str {
+        match self {
+            Action::TurnLeft => "turn_left",
+            Action::TurnRight => "turn_right",
+            Action::Thrust => "thrust",
+            Action::Reverse => "reverse",
+            Action::Fire => "fire",
+            Action::ToggleView => "toggle_view",
+            Action::SwitchWeapon => "switch_weapon",
+            Action::Hyperspace => "hyperspace",
+            Action::Trajectory => "trajectory",
+            Action::Pause => "pause",
+        }
+    }
+
+    fn index(&self) -> usize {
+        Action::ALL.iter().position(|a| a == self).unwrap()
+    }
+}
+
+/// Keys without a printable name of their own
+const KEY_NAMES: [(KeyboardKey, &str); 36] = [
+    (KeyboardKey::Apostrophe, "'"),
+    (KeyboardKey::Comma, ","),
+    (KeyboardKey::Minus, "-"),
+    (KeyboardKey::Period, "."),
+    (KeyboardKey::Slash, "/"),
+    (KeyboardKey::Semicolon, ";"),
+    (KeyboardKey::Equal, "="),
+    (KeyboardKey::LeftBracket, "["),
+    (KeyboardKey::Backslash, "\\"),
+    (KeyboardKey::RightBracket, "]"),
+    (KeyboardKey::Grave, "`"),
+    (KeyboardKey::Space, "Space"),
+    (KeyboardKey::Enter, "Enter"),
+    (KeyboardKey::Tab, "Tab"),
+    (KeyboardKey::Backspace, "Backspace"),
+    (KeyboardKey::Insert, "Insert"),
+    (KeyboardKey::Delete, "Delete"),
+    (KeyboardKey::Right, "Right"),
+    (KeyboardKey::Left, "Left"),
+    (KeyboardKey::Down, "Down"),
+    (KeyboardKey::Up, "Up"),
+    (KeyboardKey::PageUp, "PageUp"),
+    (KeyboardKey::PageDown, "PageDown"),
+    (KeyboardKey::Home, "Home"),
+    (KeyboardKey::End, "End"),
+    (KeyboardKey::CapsLock, "CapsLock"),
+    (KeyboardKey::Pause, "Pause"),
+    (KeyboardKey::LeftShift, "LeftShift"),
+    (KeyboardKey::LeftControl, "LeftControl"),
+    (KeyboardKey::LeftAlt, "LeftAlt"),
+    (KeyboardKey::RightShift, "RightShift"),
+    (KeyboardKey::RightControl, "RightControl"),
+    (KeyboardKey::RightAlt, "RightAlt"),
+    (KeyboardKey::KpEnter, "KpEnter"),
+    (KeyboardKey::KpAdd, "KpAdd"),
+    (KeyboardKey::KpSubtract, "KpSubtract"),
+];
+
+/// @brief  A readable name for a raylib key code, "-" when unbound
+pub fn key_name(key: i32) -> String {
+    let function = KeyboardKey::F1 as i32..=KeyboardKey::F12 as i32;
+    let keypad = KeyboardKey::Kp0 as i32..=KeyboardKey::Kp9 as i32;
+    match key {
+        0 => "-".to_string(),
+        48..=57 | 65..=90 => (key as u8 as char).to_string(),
+        k if function.contains(&k) => format!("F{}", k - KeyboardKey::F1 as i32 + 1),
+        k if keypad.contains(&k) => format!("Kp{}", k - KeyboardKey::Kp0 as i32),
+        k => KEY_NAMES.iter()
+            .find(|(code, _)| *code as i32 == k)
+            .map_or(format!("Key{}", k), |(_, name)| name.to_string()),
+    }
+}
+
+/// @brief  The key code for a name written by `key_name`
+pub fn key_from_name(name: &str) -> Option<i32> {
+    if name == "-" {
+        return Some(0)
+    }
+    if let Some(code) = (0..400).find(|k| *k != 0 && key_name(*k) == name) {
+        return Some(code)
+    }
+    name.strip_prefix("Key").and_then(|k| k.parse().ok())
+}
+
+/// Gamepad buttons in raylib's order, named by where they sit on the pad
+const BUTTON_NAMES: [&str; 18] = [
+    "-", "DpadUp", "DpadRight", "DpadDown", "DpadLeft", "North", "East", "South", "West",
+    "LB", "LT", "RB", "RT", "Select", "Guide", "Start", "LS", "RS",
+];
+
+/// Raylib only tracks this many gamepads
+const MAX_GAMEPADS: i32 = 4;
+
+pub fn button_name(button: i32) -> &'static str {
+    BUTTON_NAMES.get(button as usize).copied().unwrap_or("-")
+}
+
+pub fn button_from_name(name: &str) -> Option<i32> {
+    BUTTON_NAMES.iter().position(|n| *n == name).map(|i| i as i32)
+}
+
+/// @brief  The first button pressed this frame on one particular pad
+pub unsafe fn button_pressed_on(pad: i32) -> Option<i32> {
+    (1..BUTTON_NAMES.len() as i32).find(|button| IsGamepadButtonPressed(pad, *button))
+}
+
+/// @brief  The raylib id of the nth connected gamepad, so pads can come and go
+///         and each slot picks up whichever one is next in line
+pub unsafe fn connected_gamepad(nth: i32) -> Option<i32> {
+    (0..MAX_GAMEPADS).filter(|pad| IsGamepadAvailable(*pad)).nth(nth.max(0) as usize)
+}
+
+pub unsafe fn gamepad_name(pad: i32) -> String {
+    let name = GetGamepadName(pad);
+    if name.is_null() {
+        return format!("Gamepad {}", pad + 1)
+    }
+    CStr::from_ptr(name).to_string_lossy().into_owned()
+}
+
+/// The fixed navigation every menu understands, from the keyboard or any gamepad
+#[derive(Clone, Copy)]
+pub enum MenuKey {
+    Up,
+    Down,
+    Left,
+    Right,
+    Accept,
+    Back,
+    /// flips a screen between two modes, like keyboard and gamepad bindings
+    Switch,
+    Clear,
+}
+
+pub unsafe fn menu_pressed(key: MenuKey) -> bool {
+    let (keys, button): (&[KeyboardKey], GamepadButton) = match key {
+        MenuKey::Up => (&[KeyboardKey::Up, KeyboardKey::W], GamepadButton::LeftFaceUp),
+        MenuKey::Down => (&[KeyboardKey::Down, KeyboardKey::S], GamepadButton::LeftFaceDown),
+        MenuKey::Left => (&[KeyboardKey::Left, KeyboardKey::A], GamepadButton::LeftFaceLeft),
+        MenuKey::Right => (&[KeyboardKey::Right, KeyboardKey::D], GamepadButton::LeftFaceRight),
+        MenuKey::Accept => (&[KeyboardKey::Enter, KeyboardKey::Space], GamepadButton::RightFaceDown),
+        MenuKey::Back => (&[KeyboardKey::Escape, KeyboardKey::Backspace], GamepadButton::RightFaceRight),
+        MenuKey::Switch => (&[KeyboardKey::Tab], GamepadButton::RightFaceUp),
+        MenuKey::Clear => (&[KeyboardKey::Delete], GamepadButton::RightFaceLeft),
+    };
+    keys.iter().any(|k| IsKeyPressed(*k as i32))
+        || (0..MAX_GAMEPADS).any(|pad| IsGamepadAvailable(pad) && IsGamepadButtonPressed(pad, button as i32))
+}
+
+/// A key and a gamepad button per action for a single player slot, 0 leaves either unbound
+#[derive(Clone, Copy)]
+pub struct Bindings {
+    keys: [i32; Action::ALL.len()],
+    buttons: [i32; Action::ALL.len()],
+    /// which connected gamepad this slot reads, see `connected_gamepad`, negative for none
+    pub gamepad: i32,
+    pub deadzone: f32,
+}
+
+impl Bindings {
+    pub fn new(pairs: &[(Action, KeyboardKey)]) -> Bindings {
+        let mut bindings = Bindings {
+            keys: [0; Action::ALL.len()],
+            buttons: [0; Action::ALL.len()],
+            gamepad: -1,
+            deadzone: 0.25,
+        };
+        for (action, key) in pairs {
+            bindings.set(*action, *key as i32);
+        }
+        bindings
+    }
+
+    /// @brief  Reads the given connected gamepad with the usual button layout
+    pub fn with_gamepad(mut self, gamepad: i32) -> Bindings {
+        self.gamepad = gamepad;
+        for (action, button) in [
+            (Action::TurnLeft, GamepadButton::LeftFaceLeft),
+            (Action::TurnRight, GamepadButton::LeftFaceRight),
+            (Action::Thrust, GamepadButton::RightTrigger2),
+            (Action::Reverse, GamepadButton::LeftTrigger2),
+            (Action::Fire, GamepadButton::RightFaceDown),
+            (Action::ToggleView, GamepadButton::RightFaceUp),
+            (Action::SwitchWeapon, GamepadButton::RightFaceRight),
+            (Action::Hyperspace, GamepadButton::RightFaceLeft),
+            (Action::Trajectory, GamepadButton::MiddleLeft),
+            (Action::Pause, GamepadButton::MiddleRight),
+        ] {
+            self.set_button(action, button as i32);
+        }
+        self
+    }
+
+    pub fn key(&self, action: Action) -> i32 {
+        self.keys[action.index()]
+    }
+
+    pub fn set(&mut self, action: Action, key: i32) {
+        self.keys[action.index()] = key;
+    }
+
+    pub fn button(&self, action: Action) -> i32 {
+        self.buttons[action.index()]
+    }
+
+    pub fn set_button(&mut self, action: Action, button: i32) {
+        self.buttons[action.index()] = button;
+    }
+
+    /// @brief  The raylib id of this slot's gamepad while one is plugged in
+    pub unsafe fn pad(&self) -> Option<i32> {
+        if self.gamepad < 0 {
+            return None
+        }
+        connected_gamepad(self.gamepad)
+    }
+
+    pub unsafe fn down(&self, action: Action) -> bool {
+        let key = self.key(action);
+        let button = self.button(action);
+        (key != 0 && IsKeyDown(key))
+            || (button != 0 && self.pad().is_some_and(|pad| IsGamepadButtonDown(pad, button)))
+    }
+
+    pub unsafe fn pressed(&self, action: Action) -> bool {
+        let key = self.key(action);
+        let button = self.button(action);
+        (key != 0 && IsKeyPressed(key))
+            || (button != 0 && self.pad().is_some_and(|pad| IsGamepadButtonPressed(pad, button)))
+    }
+
+    /// @brief  Whether any action's key or button is held, so a menu key that
+    ///         doubles as one can wait for a fresh press
+    pub unsafe fn any_down(&self) -> bool {
+        Action::ALL.iter().any(|action| self.down(*action))
+    }
+
+    /// @brief  The left stick past the deadzone, rescaled so it still starts from zero
+    pub unsafe fn stick(&self) -> Vector2 {
+        let pad = match self.pad() {
+            Some(pad) => pad,
+            None => return Vector2 { x: 0.0, y: 0.0 },
+        };
+        let x = GetGamepadAxisMovement(pad, GamepadAxis::LeftX as i32);
+        let y = GetGamepadAxisMovement(pad, GamepadAxis::LeftY as i32);
+        let length = (x * x + y * y).sqrt();
+        if length <= self.deadzone {
+            return Vector2 { x: 0.0, y: 0.0 }
+        }
+        let scale = ((length - self.deadzone) / (1.0 - self.deadzone)).min(1.0) / length;
+        Vector2 { x: x * scale, y: y * scale }
+    }
+
+    /// @brief  Steering as a vector, x turns right and negative y thrusts
+    pub unsafe fn axis(&self) -> Vector2 {
+        let stick = self.stick();
+        let x = (self.down(Action::TurnRight) as i8 - self.down(Action::TurnLeft) as i8) as f32 + stick.x;
+        let y = (self.down(Action::Reverse) as i8 - self.down(Action::Thrust) as i8) as f32 + stick.y;
+        Vector2 { x: x.clamp(-1.0, 1.0), y: y.clamp(-1.0, 1.0) }
+    }
+}
+
+/// Bindings for every player slot, read from and written back to a config file
+pub struct InputMap {
+    pub slots: Vec<Bindings>,
+    path: PathBuf,
+}
+
+impl InputMap {
+    pub fn defaults() -> Vec<Bindings> {
+        vec![
+            Bindings::new(&[
+                (Action::TurnLeft, KeyboardKey::A),
+                (Action::TurnRight, KeyboardKey::D),
+                (Action::Thrust, KeyboardKey::W),
+                (Action::Reverse, KeyboardKey::S),
+                (Action::Fire, KeyboardKey::E),
+                (Action::ToggleView, KeyboardKey::Q),
+                (Action::SwitchWeapon, KeyboardKey::F),
+                (Action::Hyperspace, KeyboardKey::X),
+                (Action::Trajectory, KeyboardKey::R),
+                (Action::Pause, KeyboardKey::P),
+            ]).with_gamepad(0),
+            Bindings::new(&[
+                (Action::TurnLeft, KeyboardKey::Left),
+                (Action::TurnRight, KeyboardKey::Right),
+                (Action::Thrust, KeyboardKey::Up),
+                (Action::Reverse, KeyboardKey::Down),
+                (Action::Fire, KeyboardKey::Slash),
+                (Action::ToggleView, KeyboardKey::Period),
+                (Action::SwitchWeapon, KeyboardKey::Comma),
+                (Action::Hyperspace, KeyboardKey::RightControl),
+                (Action::Trajectory, KeyboardKey::Semicolon),
+                (Action::Pause, KeyboardKey::Backslash),
+            ]).with_gamepad(1),
+        ]
+    }
+
+    /// @brief  Starts from the defaults and overrides whatever the file names
+    pub fn load(path: &Path) -> InputMap {
+        let mut map = InputMap { slots: InputMap::defaults(), path: path.to_path_buf() };
+        if let Some(config) = Config::load(path) {
+            for (i, slot) in map.slots.iter_mut().enumerate() {
+                let section = format!("player_{}", i + 1);
+                for action in Action::ALL {
+                    let key = config.get_str(&section, action.config_key()).and_then(|n| key_from_name(&n));
+                    if let Some(key) = key {
+                        slot.set(action, key);
+                    }
+                    let button = config.get_str(&section, &format!("pad_{}", action.config_key()))
+                        .and_then(|n| button_from_name(&n));
+                    if let Some(button) = button {
+                        slot.set_button(action, button);
+                    }
+                }
+                slot.gamepad = config.get_or(&section, "gamepad", slot.gamepad);
+                slot.deadzone = config.get_or(&section, "deadzone", slot.deadzone).clamp(0.0, 0.9);
+            }
+        }
+        map
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let mut config = Config::new();
+        for (i, slot) in self.slots.iter().enumerate() {
+            let section = format!("player_{}", i + 1);
+            config.set(&section, "gamepad", slot.gamepad);
+            config.set(&section, "deadzone", slot.deadzone);
+            for action in Action::ALL {
+                config.set_str(&section, action.config_key(), &key_name(slot.key(action)));
+                config.set_str(&section, &format!("pad_{}", action.config_key()), button_name(slot.button(action)));
+            }
+        }
+        config.save(&self.path)
+    }
+
+    pub fn slot(&self, slot: usize) -> Bindings {
+        self.slots[slot]
+    }
+
+    pub fn reset(&mut self) {
+        self.slots = InputMap::defaults();
+    }
+
+    /// @brief  Every other binding sharing a key with this one, across all slots
+    ///         since everyone plays on the same keyboard
+    pub fn conflicts(&self, slot: usize, action: Action) -> Vec<(usize, Action)> {
+        let key = self.slots[slot].key(action);
+        if key == 0 {
+            return vec![]
+        }
+        let mut found = vec![];
+        for (i, other) in self.slots.iter().enumerate() {
+            for other_action in Action::ALL {
+                if (i, other_action) != (slot, action) && other.key(other_action) == key {
+                    found.push((i, other_action));
+                }
+            }
+        }
+        found
+    }
+
+    /// @brief  Other actions on the same gamepad sharing this action's button
+    pub fn button_conflicts(&self, slot: usize, action: Action) -> Vec<(usize, Action)> {
+        let bindings = &self.slots[slot];
+        let button = bindings.button(action);
+        if button == 0 || bindings.gamepad < 0 {
+            return vec![]
+        }
+        let mut found = vec![];
+        for (i, other) in self.slots.iter().enumerate() {
+            if other.gamepad != bindings.gamepad { continue }
+            for other_action in Action::ALL {
+                if (i, other_action) != (slot, action) && other.button(other_action) == button {
+                    found.push((i, other_action));
+                }
+            }
+        }
+        found
+    }
+
+    /// @brief  What each slot is playing with right now, for the menu
+    pub unsafe fn describe(&self, slot: usize) -> String {
+        match self.slots[slot].pad() {
+            Some(pad) => gamepad_name(pad),
+            None => "Keyboard".to_string(),
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use raylib_ffi::*;
+use crate::rl::phosphor::Stroke;
+use crate::rl::physics;
+use crate::rl::vector::vector2 as v;
+use std::f32::consts::PI;
+use rand::Rng;
+
+/// How a burst of particles is thrown out and how it looks as it dies
+#[derive(Clone, Copy)]
+pub struct Emitter {
+    pub count: usize,
+    /// slowest and fastest launch speed
+    pub speed: (f32, f32),
+    /// total angle the burst fans out over, 2π for every direction
+    pub spread: f32,
+    /// shortest and longest lifetime in seconds
+    pub life: (f32, f32),
+    pub color_start: Color,
+    pub color_end: Color,
+    pub size: f32,
+    /// above zero each particle is a spinning line of this length instead of a dot
+    pub length: f32,
+    /// pulled towards the star like everything else in the arena
+    pub gravity: bool,
+    /// crosses the arena edge and comes back on the other side
+    pub wrap: bool,
+}
+
+#[derive(Clone, Copy)]
+struct Particle {
+    position: Vector2,
+    velocity: Vector2,
+    angle: f32,
+    spin: f32,
+    life: f32,
+    max_life: f32,
+    emitter: Emitter,
+}
+
+/// A fixed pool of particles, nothing is allocated once it is created and new
+/// particles are dropped while the pool is full
+pub struct Particles {
+    pool: Vec<Particle>,
+    alive: usize,
+}
+
+fn lerp_color(a: Color, b: Color, t: f32) -> Color {
+    let mix = |x: u8, y: u8| (x as f32 + (y as f32 - x as f32) * t) as u8;
+    Color { r: mix(a.r, b.r), g: mix(a.g, b.g), b: mix(a.b, b.b), a: mix(a.a, b.a) }
+}
+
+impl Particles {
+    pub fn new(capacity: usize) -> Particles {
+        let blank = Particle {
+            position: v::zero(),
+            velocity: v::zero(),
+            angle: 0.0,
+            spin: 0.0,
+            life: 0.0,
+            max_life: 0.0,
+            emitter: Emitter {
+                count: 0, speed: (0.0, 0.0), spread: 0.0, life: (0.0, 0.0),
+                color_start: Color { r: 0, g: 0, b: 0, a: 0 }, color_end: Color { r: 0, g: 0, b: 0, a: 0 },
+                size: 0.0, length: 0.0, gravity: false, wrap: false,
+            },
+        };
+        Particles { pool: vec![blank; capacity], alive: 0 }
+    }
+
+    pub fn clear(&mut self) {
+        self.alive = 0;
+    }
+
+    /// @brief  A burst of `emitter.count` particles fanned around `direction`,
+    ///         on top of the velocity of whatever they came from
+    pub fn burst(&mut self, emitter: &Emitter, position: Vector2, direction: f32, inherit: Vector2) {
+        self.spawn(emitter, emitter.count, position, direction, inherit);
+    }
+
+    /// @brief  A steady stream, `emitter.count` is particles per second here
+    /// @note   Fractions of a particle are rolled for, so the rate holds at any frame rate
+    pub fn stream(&mut self, emitter: &Emitter, position: Vector2, direction: f32, inherit: Vector2, delta: f32) {
+        let expected = emitter.count as f32 * delta;
+        let count = expected as usize + (rand::thread_rng().gen::<f32>() < expected.fract()) as usize;
+        self.spawn(emitter, count, position, direction, inherit);
+    }
+
+    fn spawn(&mut self, emitter: &Emitter, count: usize, position: Vector2, direction: f32, inherit: Vector2) {
+        let mut rng = rand::thread_rng();
+        for _ in 0..count {
+            if self.alive == self.pool.len() { return }
+            let angle = direction + (rng.gen::<f32>() - 0.5) * emitter.spread;
+            let speed = emitter.speed.0 + rng.gen::<f32>() * (emitter.speed.1 - emitter.speed.0);
+            let life = emitter.life.0 + rng.gen::<f32>() * (emitter.life.1 - emitter.life.0);
+            self.pool[self.alive] = Particle {
+                position,
+                velocity: v::add(&inherit, &v::from_angle(angle, speed)),
+                angle: rng.gen::<f32>() * 2.0 * PI,
+                spin: (rng.gen::<f32>() - 0.5) * 4.0 * PI,
+                life,
+                max_life: life,
+                emitter: *emitter,
+            };
+            self.alive += 1;
+        }
+    }
+
+    pub fn update(&mut self, main_star: &Vector2, bounds: (f32, f32), delta: f32) {
+        let mut i = 0;
+        while i < self.alive {
+            let p = &mut self.pool[i];
+            p.life -= delta;
+            if p.life <= 0.0 {
+                // swap the last live particle into the gap instead of shifting the rest
+                self.alive -= 1;
+                self.pool.swap(i, self.alive);
+                continue
+            }
+            if p.emitter.gravity {
+                p.velocity = v::add(&p.velocity, &v::scale(&physics::gravity(&p.position, main_star), delta));
+            }
+            p.position = v::add(&p.position, &v::scale(&p.velocity, delta));
+            if p.emitter.wrap {
+                p.position = v::wrap(&p.position, bounds.0, bounds.1);
+            }
+            p.angle += p.spin * delta;
+            i += 1;
+        }
+    }
+
+    pub unsafe fn draw(&self, stroke: Stroke) {
+        for p in self.pool[..self.alive].iter() {
+            let color = lerp_color(p.emitter.color_end, p.emitter.color_start, p.life / p.max_life);
+            if p.emitter.length > 0.0 {
+                let half = v::from_angle(p.angle, p.emitter.length / 2.0);
+                stroke.line(v::sub(&p.position, &half), v::add(&p.position, &half), color);
+            } else {
+                DrawCircleV(p.position, p.emitter.size, color);
+            }
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use raylib_ffi::*;
+use colors::*;
+use crate::rl::post::{fade_keep, PostChain};
+use std::ffi::c_int;
+
+/// How outlines are drawn, plain lines or the glowing beam of a vector display
+#[derive(Clone, Copy, PartialEq)]
+pub enum Stroke {
+    Line,
+    Beam,
+}
+
+impl Stroke {
+    pub unsafe fn line(&self, start: Vector2, end: Vector2, color: Color) {
+        match self {
+            Stroke::Line => DrawLineV(start, end, color),
+            Stroke::Beam => {
+                // a soft halo either side of a core driven hotter than the colour itself
+                DrawLineEx(start, end, 6.0, Fade(color, 0.12));
+                DrawLineEx(start, end, 3.0, Fade(color, 0.35));
+                DrawLineEx(start, end, 1.5, ColorBrightness(color, 0.4));
+            },
+        }
+    }
+
+    pub unsafe fn circle(&self, center: Vector2, radius: f32, color: Color) {
+        match self {
+            Stroke::Line => DrawCircleLines(center.x as i32, center.y as i32, radius, color),
+            Stroke::Beam => {
+                DrawRing(center, radius - 3.0, radius + 3.0, 0.0, 360.0, 32, Fade(color, 0.12));
+                DrawRing(center, radius - 0.75, radius + 0.75, 0.0, 360.0, 32, ColorBrightness(color, 0.4));
+            },
+        }
+    }
+}
+
+/// The Type 30 look, everything drawn between `begin` and `end` lands on a
+/// screen that keeps glowing after the beam moves on and fades exponentially
+pub struct Phosphor {
+    beams: RenderTexture2D,
+    decay: PostChain,
+}
+
+impl Phosphor {
+    pub unsafe fn new(width: i32, height: i32) -> Phosphor {
+        Phosphor {
+            beams: LoadRenderTexture(width, height),
+            decay: PostChain::new(&["persistence"], width, height),
+        }
+    }
+
+    /// @brief  Starts this frame's beams, call outside any other texture mode
+    pub unsafe fn begin(&self) {
+        BeginTextureMode(self.beams);
+        ClearBackground(BLACK);
+    }
+
+    /// @brief  Folds this frame's beams into the glow, returns the texture to show
+    /// @note   `persistence` is the seconds a trail takes to drop to about a third,
+    ///         without the persistence shader the beams are shown without trails
+    pub unsafe fn end(&mut self, persistence: f32, delta: f32) -> Texture2D {
+        EndTextureMode();
+        self.decay.set("keep", fade_keep(persistence, delta));
+        self.decay.apply(self.beams.texture)
+    }
+
+    /// @brief  Wipes the glow so a new round doesn't start under the last one's trails
+    pub unsafe fn clear(&self) {
+        self.decay.clear_history();
+    }
+
+    /// @brief  Lays the glowing screen over whatever is already drawn, like light would
+    pub unsafe fn draw(texture: Texture2D) {
+        let source = Rectangle { x: 0.0, y: 0.0, width: texture.width as f32, height: -texture.height as f32 };
+        BeginBlendMode(enums::BlendMode::Additive as c_int);
+        DrawTextureRec(texture, source, Vector2 { x: 0.0, y: 0.0 }, WHITE);
+        EndBlendMode();
+    }
+
+    pub unsafe fn unload(self) {
+        UnloadRenderTexture(self.beams);
+        self.decay.unload();
+    }
+}
@@ -0,0 +1,180 @@
+use raylib_ffi::Vector2;
+use crate::rl::vector::vector2 as v;
+
+/// Strength of the star's pull, tuned to match the old per-frame impulse at 144 fps
+pub const STAR_MU: f32 = 6.6743 * 10.0 * 144.0;
+/// Distance below which the pull stops growing, keeps close passes finite
+pub const SOFTENING: f32 = 5.0;
+/// Longest step the integrators take, frames longer than this are split up
+pub const MAX_STEP: f32 = 1.0 / 240.0;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Integrator {
+    SemiImplicitEuler,
+    VelocityVerlet,
+    Rk4,
+}
+
+impl Integrator {
+    pub const ALL: [Integrator; 3] = [Integrator::SemiImplicitEuler, Integrator::VelocityVerlet, Integrator::Rk4];
+
+    pub fn next(&self) -> Integrator {
+        match self {
+            Integrator::SemiImplicitEuler => Integrator::VelocityVerlet,
+            Integrator::VelocityVerlet => Integrator::Rk4,
+            Integrator::Rk4 => Integrator::SemiImplicitEuler,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Integrator::SemiImplicitEuler => "Semi-Implicit Euler",
+            Integrator::VelocityVerlet => "Velocity Verlet",
+            Integrator::Rk4 => "RK4",
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Body {
+    pub position: Vector2,
+    pub velocity: Vector2,
+}
+
+/// @brief  Softened acceleration toward the star
+/// @note   The game has always used a 1/r pull, this keeps it but removes the singularity
+pub fn gravity(obj: &Vector2, star: &Vector2) -> Vector2 {
+    let d = v::sub(star, obj);
+    let dist_s = d.x * d.x + d.y * d.y + SOFTENING * SOFTENING;
+    v::scale(&d, STAR_MU / dist_s)
+}
+
+/// @brief  Potential energy per unit mass matching `gravity`
+pub fn potential(obj: &Vector2, star: &Vector2) -> f32 {
+    let d = v::sub(obj, star);
+    STAR_MU / 2.0 * (d.x * d.x + d.y * d.y + SOFTENING * SOFTENING).ln()
+}
+
+pub fn energy(body: &Body, star: &Vector2) -> f32 {
+    let speed = v::len(&body.velocity);
+    0.5 * speed * speed + potential(&body.position, star)
+}
+
+pub fn angular_momentum(body: &Body, star: &Vector2) -> f32 {
+    let r = v::sub(&body.position, star);
+    r.x * body.velocity.y - r.y * body.velocity.x
+}
+
+/// @brief  Speed needed for a circular orbit at the given distance
+pub fn orbit_speed(radius: f32) -> f32 {
+    (STAR_MU * radius * radius / (radius * radius + SOFTENING * SOFTENING)).sqrt()
+}
+
+fn accel(pos: &Vector2, star: &Vector2, thrust: &Vector2) -> Vector2 {
+    v::add(&gravity(pos, star), thrust)
+}
+
+/// @brief  Advances the body by a single step of `dt`
+pub fn step(body: &mut Body, star: &Vector2, thrust: &Vector2, integrator: Integrator, dt: f32) {
+    match integrator {
+        Integrator::SemiImplicitEuler => {
+            body.velocity = v::add(&body.velocity, &v::scale(&accel(&body.position, star, thrust), dt));
+            body.position = v::add(&body.position, &v::scale(&body.velocity, dt));
+        },
+        Integrator::VelocityVerlet => {
+            let a0 = accel(&body.position, star, thrust);
+            body.position = v::add(
+                &body.position,
+                &v::add(&v::scale(&body.velocity, dt), &v::scale(&a0, 0.5 * dt * dt))
+            );
+            let a1 = accel(&body.position, star, thrust);
+            body.velocity = v::add(&body.velocity, &v::scale(&v::add(&a0, &a1), 0.5 * dt));
+        },
+        Integrator::Rk4 => {
+            let x0 = body.position;
+            let v0 = body.velocity;
+            let k1x = v0;
+            let k1v = accel(&x0, star, thrust);
+            let k2x = v::add(&v0, &v::scale(&k1v, dt / 2.0));
+            let k2v = accel(&v::add(&x0, &v::scale(&k1x, dt / 2.0)), star, thrust);
+            let k3x = v::add(&v0, &v::scale(&k2v, dt / 2.0));
+            let k3v = accel(&v::add(&x0, &v::scale(&k2x, dt / 2.0)), star, thrust);
+            let k4x = v::add(&v0, &v::scale(&k3v, dt));
+            let k4v = accel(&v::add(&x0, &v::scale(&k3x, dt)), star, thrust);
+            let dx = v::add(&v::add(&k1x, &v::scale(&k2x, 2.0)), &v::add(&v::scale(&k3x, 2.0), &k4x));
+            let dv = v::add(&v::add(&k1v, &v::scale(&k2v, 2.0)), &v::add(&v::scale(&k3v, 2.0), &k4v));
+            body.position = v::add(&x0, &v::scale(&dx, dt / 6.0));
+            body.velocity = v::add(&v0, &v::scale(&dv, dt / 6.0));
+        },
+    }
+}
+
+/// @brief  Advances the body by `delta`, split into equal steps no longer than `MAX_STEP`
+pub fn integrate(body: &mut Body, star: &Vector2, thrust: &Vector2, integrator: Integrator, delta: f32) {
+    if delta <= 0.0 { return }
+    let steps = (delta / MAX_STEP).ceil().max(1.0);
+    let dt = delta / steps;
+    for _ in 0..steps as usize {
+        step(body, star, thrust, integrator, dt);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STAR: Vector2 = Vector2 { x: 256.0, y: 256.0 };
+
+    fn circular(radius: f32) -> Body {
+        Body {
+            position: Vector2 { x: STAR.x + radius, y: STAR.y },
+            velocity: Vector2 { x: 0.0, y: orbit_speed(radius) },
+        }
+    }
+
+    fn drift(integrator: Integrator, seconds: f32) -> (f32, f32, f32) {
+        let mut body = circular(100.0);
+        let e0 = energy(&body, &STAR);
+        let l0 = angular_momentum(&body, &STAR);
+        let frame = 1.0 / 144.0;
+        for _ in 0..(seconds / frame) as usize {
+            integrate(&mut body, &STAR, &v::zero(), integrator, frame);
+        }
+        (
+            ((energy(&body, &STAR) - e0) / e0).abs(),
+            ((angular_momentum(&body, &STAR) - l0) / l0).abs(),
+            (v::dist(&body.position, &STAR) - 100.0).abs(),
+        )
+    }
+
+    #[test]
+    fn integrators_conserve_energy_and_momentum() {
+        for integrator in [Integrator::SemiImplicitEuler, Integrator::VelocityVerlet, Integrator::Rk4] {
+            let (energy, momentum, radius) = drift(integrator, 120.0);
+            assert!(energy < 1e-3, "energy drifted by {}", energy);
+            assert!(momentum < 1e-3, "angular momentum drifted by {}", momentum);
+            assert!(radius < 1.0, "orbit radius drifted by {}", radius);
+        }
+    }
+
+    #[test]
+    fn frame_rate_does_not_change_outcome() {
+        let mut slow = circular(80.0);
+        let mut fast = circular(80.0);
+        for _ in 0..30 {
+            integrate(&mut slow, &STAR, &v::zero(), Integrator::VelocityVerlet, 1.0 / 30.0);
+        }
+        for _ in 0..144 {
+            integrate(&mut fast, &STAR, &v::zero(), Integrator::VelocityVerlet, 1.0 / 144.0);
+        }
+        assert!(v::dist(&slow.position, &fast.position) < 0.5);
+    }
+
+    #[test]
+    fn gravity_is_finite_at_the_star() {
+        let pull = gravity(&STAR, &STAR);
+        assert!(pull.x.is_finite() && pull.y.is_finite());
+        let close = gravity(&Vector2 { x: STAR.x + 0.01, y: STAR.y }, &STAR);
+        assert!(v::len(&close) < STAR_MU / SOFTENING);
+    }
+}
@@ -0,0 +1,91 @@
+use raylib_ffi::*;
+use colors::*;
+use std::fs;
+use std::path::Path;
+
+/// The bundled catalogue, `name,ra,dec,mag` with ra in hours and dec in degrees
+pub const CATALOG_PATH: &str = "catalog/bright_stars.csv";
+
+/// Degrees of sky across the arena, the original showed the band within 22.5° of the equator
+const FIELD: f32 = 45.0;
+/// Degrees of right ascension the sky turns by each second, a full turn takes 20 minutes
+const TURN_RATE: f32 = 0.3;
+/// Faintest star drawn and the brightest in the sky, everything between is scaled along it
+/// @note   A catalogue that stops short of `FAINTEST` is scaled from its own faintest star,
+///         so the dimmest stars it has still come out dim
+const FAINTEST: f32 = 5.0;
+const BRIGHTEST: f32 = -1.5;
+
+struct CatalogStar {
+    /// right ascension and declination in degrees
+    ra: f32,
+    dec: f32,
+    magnitude: f32,
+}
+
+/// The "Expensive Planetarium", real stars from a catalogue scrolling slowly past
+/// the way the sky turned behind the original game
+pub struct Planetarium {
+    /// how bright the whole sky is drawn, 0 hides it
+    pub brightness: f32,
+    stars: Vec<CatalogStar>,
+    /// magnitude drawn dimmest, `FAINTEST` or the catalogue's own limit if that's brighter
+    faintest: f32,
+    width: f32,
+    height: f32,
+    /// right ascension at the middle of the arena
+    ra: f32,
+}
+
+impl Planetarium {
+    /// @brief  Reads the catalogue, an unreadable line is skipped and a missing file leaves the sky empty
+    pub fn load(path: &Path, width: i32, height: i32) -> Planetarium {
+        let mut stars = vec![];
+        match fs::read_to_string(path) {
+            Ok(text) => {
+                for line in text.lines().map(|l| l.trim()) {
+                    if line.is_empty() || line.starts_with('#') || line.starts_with("name,") { continue }
+                    match parse_star(line) {
+                        Some(star) => stars.push(star),
+                        None => eprintln!("skipping star catalogue line {}", line),
+                    }
+                }
+            },
+            Err(e) => eprintln!("could not read star catalogue {}: {}", path.display(), e),
+        }
+        stars.retain(|s| s.magnitude <= FAINTEST);
+        let faintest = stars.iter().map(|s| s.magnitude).fold(BRIGHTEST + 1.0, f32::max);
+        Planetarium { brightness: 1.0, stars, faintest, width: width as f32, height: height as f32, ra: 0.0 }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stars.is_empty()
+    }
+
+    pub fn update(&mut self, delta: f32) {
+        self.ra = (self.ra + TURN_RATE * delta).rem_euclid(360.0);
+    }
+
+    /// @brief  A flat projection of the band around the view, east to the left as seen looking south
+    pub unsafe fn draw(&self) {
+        if self.brightness <= 0.0 { return }
+        let scale = self.width / FIELD;
+        for star in self.stars.iter() {
+            // the nearest way round to the middle of the view, so the sky wraps at 0h
+            let ra = (star.ra - self.ra + 540.0).rem_euclid(360.0) - 180.0;
+            let position = Vector2 { x: self.width / 2.0 - ra * scale, y: self.height / 2.0 - star.dec * scale };
+            if position.x < -2.0 || position.x > self.width + 2.0 || position.y < -2.0 || position.y > self.height + 2.0 {
+                continue
+            }
+            let shine = ((self.faintest - star.magnitude) / (self.faintest - BRIGHTEST)).clamp(0.0, 1.0);
+            DrawCircleV(position, 0.6 + 1.6 * shine, Fade(WHITE, (0.3 + 0.7 * shine) * self.brightness));
+        }
+    }
+}
+
+fn parse_star(line: &str) -> Option<CatalogStar> {
+    let fields: Vec<&str> = line.split(',').map(|f| f.trim()).collect();
+    if fields.len() != 4 { return None }
+    let ra: f32 = fields[1].parse().ok()?;
+    Some(CatalogStar { ra: ra * 15.0, dec: fields[2].parse().ok()?, magnitude: fields[3].parse().ok()? })
+}
@@ -0,0 +1,245 @@
+use raylib_ffi::*;
+use colors::*;
+use crate::rl::config::Config;
+use std::ffi::{c_int, c_void};
+use std::path::Path;
+use std::ptr;
+
+/// Where pass shaders are looked up, `<name>.fs` inside this directory
+pub const SHADER_DIR: &str = "shaders";
+
+/// The chain the game ships with, read from the shader directory when there's no post.toml of your own
+pub const DEFAULT_CONFIG: &str = "post.toml";
+
+/// The chain used when no config can be read at all, the look the game always had
+pub const DEFAULT_CHAIN: [&str; 1] = ["scanlines"];
+
+/// @brief  How much of last frame a fading pass keeps, for a fade taking `seconds`
+///         to drop to about a third whatever the frame rate
+pub fn fade_keep(seconds: f32, delta: f32) -> f32 {
+    (-delta / seconds.max(0.01)).exp()
+}
+
+/// A value for a shader uniform, tagged with the GLSL type it is sent as
+#[derive(Clone, Copy)]
+pub enum Uniform {
+    Float(f32),
+    Int(i32),
+    Vec2([f32; 2]),
+    Vec3([f32; 3]),
+    Vec4([f32; 4]),
+}
+
+impl From<f32> for Uniform {
+    fn from(value: f32) -> Uniform { Uniform::Float(value) }
+}
+
+impl From<i32> for Uniform {
+    fn from(value: i32) -> Uniform { Uniform::Int(value) }
+}
+
+impl From<Vector2> for Uniform {
+    fn from(value: Vector2) -> Uniform { Uniform::Vec2([value.x, value.y]) }
+}
+
+impl From<Color> for Uniform {
+    fn from(c: Color) -> Uniform {
+        Uniform::Vec4([c.r as f32 / 255.0, c.g as f32 / 255.0, c.b as f32 / 255.0, c.a as f32 / 255.0])
+    }
+}
+
+impl Uniform {
+    /// @brief  Reads a number, or a comma separated list of two to four for a vector
+    pub fn parse(text: &str) -> Option<Uniform> {
+        let values: Vec<f32> = text.trim_matches('"')
+            .split(',')
+            .map(|v| v.trim().parse().ok())
+            .collect::<Option<Vec<f32>>>()?;
+        match values[..] {
+            [x] => Some(Uniform::Float(x)),
+            [x, y] => Some(Uniform::Vec2([x, y])),
+            [x, y, z] => Some(Uniform::Vec3([x, y, z])),
+            [x, y, z, w] => Some(Uniform::Vec4([x, y, z, w])),
+            _ => None,
+        }
+    }
+
+    unsafe fn send(&self, shader: Shader, location: c_int) {
+        use enums::ShaderUniformDataType as T;
+        let (value, kind) = match self {
+            Uniform::Float(v) => (v as *const f32 as *const c_void, T::Float),
+            Uniform::Int(v) => (v as *const i32 as *const c_void, T::Int),
+            Uniform::Vec2(v) => (v.as_ptr() as *const c_void, T::Vec2),
+            Uniform::Vec3(v) => (v.as_ptr() as *const c_void, T::Vec3),
+            Uniform::Vec4(v) => (v.as_ptr() as *const c_void, T::Vec4),
+        };
+        SetShaderValue(shader, location, value, kind as c_int);
+    }
+}
+
+/// One fragment shader run over the whole frame
+pub struct Pass {
+    pub name: String,
+    shader: Shader,
+    locations: Vec<(String, c_int)>,
+    /// last frame's output, only kept for passes that sample a `history` texture
+    history: Option<RenderTexture2D>,
+}
+
+impl Pass {
+    /// @brief  Compiles `shaders/<name>.fs` against raylib's default vertex shader
+    /// @note   raylib hands back its default shader when compiling fails, which
+    ///         is caught here so a broken pass drops out instead of blanking the screen
+    pub unsafe fn load(name: &str, width: i32, height: i32) -> Option<Pass> {
+        let path = Path::new(SHADER_DIR).join(format!("{}.fs", name));
+        if !path.exists() {
+            eprintln!("post pass {} skipped, {} not found", name, path.display());
+            return None
+        }
+        let fallback = LoadShader(ptr::null(), ptr::null());
+        let shader = LoadShader(ptr::null(), rl_str!(path.display()));
+        if !IsShaderReady(shader) || shader.id == fallback.id {
+            eprintln!("post pass {} skipped, {} failed to compile", name, path.display());
+            return None
+        }
+        let mut pass = Pass { name: name.to_string(), shader, locations: vec![], history: None };
+        if pass.location("history") >= 0 {
+            pass.history = Some(LoadRenderTexture(width, height));
+        }
+        pass.set("resolution", Uniform::Vec2([width as f32, height as f32]));
+        Some(pass)
+    }
+
+    /// @brief  The uniform's location, looked up once and -1 when the shader lacks it
+    fn location(&mut self, name: &str) -> c_int {
+        if let Some((_, location)) = self.locations.iter().find(|(n, _)| n == name) {
+            return *location
+        }
+        let location = unsafe { GetShaderLocation(self.shader, rl_str!(name)) };
+        self.locations.push((name.to_string(), location));
+        location
+    }
+
+    /// @brief  Sets a uniform, quietly ignored when the shader doesn't declare it
+    pub unsafe fn set(&mut self, name: &str, value: impl Into<Uniform>) {
+        let location = self.location(name);
+        if location >= 0 {
+            value.into().send(self.shader, location);
+        }
+    }
+
+    unsafe fn unload(self) {
+        if let Some(history) = self.history {
+            UnloadRenderTexture(history);
+        }
+        UnloadShader(self.shader);
+    }
+}
+
+/// An ordered list of full screen passes, each reading what the one before drew
+pub struct PostChain {
+    passes: Vec<Pass>,
+    buffers: [RenderTexture2D; 2],
+}
+
+impl PostChain {
+    pub unsafe fn new(names: &[&str], width: i32, height: i32) -> PostChain {
+        let buffers = [LoadRenderTexture(width, height), LoadRenderTexture(width, height)];
+        // the last buffer written is what gets scaled into the window
+        for buffer in buffers.iter() {
+            SetTextureFilter(buffer.texture, enums::TextureFilter::Bilinear as c_int);
+        }
+        PostChain {
+            passes: names.iter().filter_map(|name| Pass::load(name, width, height)).collect(),
+            buffers,
+        }
+    }
+
+    /// @brief  Builds the chain named by `passes` in the file, then sets any uniforms
+    ///         listed under a section named after a pass, e.g. `[bloom] strength = 0.5`
+    /// @note   Without the file the shipped `shaders/post.toml` is used instead
+    pub unsafe fn load(path: &Path, width: i32, height: i32) -> PostChain {
+        let shipped = Path::new(SHADER_DIR).join(DEFAULT_CONFIG);
+        let config = match Config::load(path).or_else(|| Config::load(&shipped)) {
+            Some(config) => config,
+            None => return PostChain::new(&DEFAULT_CHAIN, width, height),
+        };
+        let list = config.get_str("", "passes").unwrap_or_else(|| DEFAULT_CHAIN.join(","));
+        let names: Vec<&str> = list.split(',').map(|n| n.trim()).filter(|n| !n.is_empty()).collect();
+        let mut chain = PostChain::new(&names, width, height);
+        for pass in chain.passes.iter_mut() {
+            for key in config.keys(&pass.name) {
+                match config.get::<String>(&pass.name, &key).and_then(|v| Uniform::parse(&v)) {
+                    Some(value) => pass.set(&key, value),
+                    None => eprintln!("post pass {} has an unreadable value for {}", pass.name, key),
+                }
+            }
+        }
+        chain
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.passes.is_empty()
+    }
+
+    pub fn pass_mut(&mut self, name: &str) -> Option<&mut Pass> {
+        self.passes.iter_mut().find(|p| p.name == name)
+    }
+
+    /// @brief  Sets a uniform on every pass that declares it, like `time`
+    pub unsafe fn set(&mut self, name: &str, value: impl Into<Uniform> + Copy) {
+        for pass in self.passes.iter_mut() {
+            pass.set(name, value);
+        }
+    }
+
+    /// @brief  Runs every pass over the frame, returns the texture holding the result
+    /// @note   Call outside BeginDrawing, the passes render into their own targets
+    pub unsafe fn apply(&mut self, frame: Texture2D) -> Texture2D {
+        let mut source = frame;
+        for (i, pass) in self.passes.iter_mut().enumerate() {
+            let target = self.buffers[i % 2];
+            BeginTextureMode(target);
+                ClearBackground(BLACK);
+                BeginShaderMode(pass.shader);
+                    if let Some(history) = pass.history {
+                        let location = pass.location("history");
+                        SetShaderValueTexture(pass.shader, location, history.texture);
+                    }
+                    draw_flipped(source);
+                EndShaderMode();
+            EndTextureMode();
+            if let Some(history) = pass.history {
+                BeginTextureMode(history);
+                    draw_flipped(target.texture);
+                EndTextureMode();
+            }
+            source = target.texture;
+        }
+        source
+    }
+
+    /// @brief  Forgets what every pass with a `history` texture drew before
+    pub unsafe fn clear_history(&self) {
+        for history in self.passes.iter().filter_map(|p| p.history) {
+            BeginTextureMode(history);
+                ClearBackground(BLACK);
+            EndTextureMode();
+        }
+    }
+
+    pub unsafe fn unload(self) {
+        for pass in self.passes {
+            pass.unload();
+        }
+        for buffer in self.buffers {
+            UnloadRenderTexture(buffer);
+        }
+    }
+}
+
+/// @brief  Copies a render texture into the current target the right way up
+unsafe fn draw_flipped(texture: Texture2D) {
+    let source = Rectangle { x: 0.0, y: 0.0, width: texture.width as f32, height: -texture.height as f32 };
+    DrawTextureRec(texture, source, Vector2 { x: 0.0, y: 0.0 }, WHITE);
+}
@@ -0,0 +1,161 @@
+use raylib_ffi::*;
+use colors::*;
+use crate::rl::vector::vector2 as v;
+use noise::{NoiseFn, Perlin};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use std::f32::consts::PI;
+use std::ffi::c_int;
+use std::slice;
+
+/// One depth of the sky, further layers are fuller, dimmer and slower
+struct LayerSpec {
+    /// stars per 10,000 pixels at a density of 1
+    stars: f32,
+    /// dimmest and brightest alpha
+    alpha: (u8, u8),
+    /// stars are drawn as size x size squares
+    size: i32,
+    /// pixels a second the layer scrolls by
+    drift: f32,
+    /// share of the stars left out of the texture to twinkle on their own
+    twinkle: f32,
+}
+
+const LAYERS: [LayerSpec; 3] = [
+    LayerSpec { stars: 12.0, alpha: (40, 140), size: 1, drift: 1.5, twinkle: 0.0 },
+    LayerSpec { stars: 5.0, alpha: (100, 220), size: 1, drift: 4.0, twinkle: 0.1 },
+    LayerSpec { stars: 1.2, alpha: (180, 255), size: 2, drift: 9.0, twinkle: 0.3 },
+];
+
+/// Which way the whole sky scrolls, every layer shares it so the depth reads as parallax
+const DRIFT: Vector2 = Vector2 { x: -0.97, y: 0.24 };
+
+/// Size of the perlin clumps stars gather in, smaller is larger
+const CLUMPING: f64 = 0.012;
+
+struct Twinkle {
+    position: Vector2,
+    alpha: f32,
+    rate: f32,
+    phase: f32,
+}
+
+struct Layer {
+    texture: Texture2D,
+    offset: Vector2,
+    drift: f32,
+    size: f32,
+    twinkles: Vec<Twinkle>,
+}
+
+/// The background sky, a few layers of stars baked into wrapping textures that
+/// drift past at different speeds, with a handful left out to twinkle
+pub struct Starfield {
+    /// how many stars there are, 1 is the usual sky
+    pub density: f32,
+    /// how bright the whole sky is drawn, 0 hides it
+    pub brightness: f32,
+    /// the sky is rebuilt from this when only the density changes
+    seed: u32,
+    width: i32,
+    height: i32,
+    layers: Vec<Layer>,
+    time: f32,
+}
+
+impl Starfield {
+    pub unsafe fn new(width: i32, height: i32, seed: u32, density: f32) -> Starfield {
+        let mut starfield = Starfield { density, brightness: 1.0, seed, width, height, layers: vec![], time: 0.0 };
+        starfield.generate(seed, density);
+        starfield
+    }
+
+    /// @brief  Throws the old sky away and scatters a new one from `seed`
+    /// @note   Stars are written straight into an image's pixels and uploaded once
+    ///         per layer, instead of a draw call for every pixel of the arena
+    pub unsafe fn generate(&mut self, seed: u32, density: f32) {
+        self.unload_layers();
+        self.seed = seed;
+        self.density = density;
+        let mut rng = StdRng::seed_from_u64(seed as u64);
+        let perlin = Perlin::new(seed);
+        let (width, height) = (self.width, self.height);
+        let area = (width * height) as f32 / 10_000.0;
+        for spec in LAYERS.iter() {
+            let image = GenImageColor(width, height, BLANK);
+            let pixels = slice::from_raw_parts_mut(image.data as *mut Color, (width * height) as usize);
+            let mut twinkles = vec![];
+            for _ in 0..(spec.stars * area * density) as usize {
+                let (x, y) = (rng.gen_range(0..width), rng.gen_range(0..height));
+                // keep the clumpy look of the old noise sky by thinning out the gaps
+                let clump = (perlin.get([x as f64 * CLUMPING, y as f64 * CLUMPING]) as f32 + 1.0) / 2.0;
+                if rng.gen::<f32>() > 0.3 + 0.7 * clump { continue }
+                let alpha = rng.gen_range(spec.alpha.0..=spec.alpha.1);
+                if rng.gen::<f32>() < spec.twinkle {
+                    twinkles.push(Twinkle {
+                        position: Vector2 { x: x as f32, y: y as f32 },
+                        alpha: alpha as f32 / 255.0,
+                        rate: rng.gen_range(1.5..4.0),
+                        phase: rng.gen::<f32>() * 2.0 * PI,
+                    });
+                    continue
+                }
+                // stars on an edge spill over to the other side so the texture tiles
+                for dx in 0..spec.size {
+                    for dy in 0..spec.size {
+                        let i = ((y + dy) % height * width + (x + dx) % width) as usize;
+                        pixels[i] = Color { r: 255, g: 255, b: 255, a: pixels[i].a.max(alpha) };
+                    }
+                }
+            }
+            let texture = LoadTextureFromImage(image);
+            UnloadImage(image);
+            SetTextureWrap(texture, enums::TextureWrap::Repeat as c_int);
+            self.layers.push(Layer { texture, offset: v::zero(), drift: spec.drift, size: spec.size as f32, twinkles });
+        }
+    }
+
+    /// @brief  The same sky with more or fewer stars
+    pub unsafe fn set_density(&mut self, density: f32) {
+        self.generate(self.seed, density);
+    }
+
+    pub fn update(&mut self, delta: f32) {
+        self.time += delta;
+        let (width, height) = (self.width as f32, self.height as f32);
+        for layer in self.layers.iter_mut() {
+            // wrapped so the offset never grows large enough to lose precision
+            layer.offset.x = (layer.offset.x + DRIFT.x * layer.drift * delta).rem_euclid(width);
+            layer.offset.y = (layer.offset.y + DRIFT.y * layer.drift * delta).rem_euclid(height);
+        }
+    }
+
+    pub unsafe fn draw(&self) {
+        if self.brightness <= 0.0 { return }
+        let (width, height) = (self.width as f32, self.height as f32);
+        for layer in self.layers.iter() {
+            // the texture repeats, so sliding the source rectangle scrolls it
+            let source = Rectangle { x: -layer.offset.x, y: -layer.offset.y, width, height };
+            DrawTextureRec(layer.texture, source, v::zero(), Fade(WHITE, self.brightness));
+            for star in layer.twinkles.iter() {
+                let position = Vector2 {
+                    x: (star.position.x + layer.offset.x).rem_euclid(width),
+                    y: (star.position.y + layer.offset.y).rem_euclid(height),
+                };
+                let flicker = 0.65 + 0.35 * (self.time * star.rate + star.phase).sin();
+                let size = Vector2 { x: layer.size, y: layer.size };
+                DrawRectangleV(position, size, Fade(WHITE, star.alpha * flicker * self.brightness));
+            }
+        }
+    }
+
+    unsafe fn unload_layers(&mut self) {
+        for layer in self.layers.drain(..) {
+            UnloadTexture(layer.texture);
+        }
+    }
+
+    pub unsafe fn unload(mut self) {
+        self.unload_layers();
+    }
+}
@@ -1,3 +1,4 @@
+#[derive(Clone)]
 pub struct Timer {
     pub timer: f32,
     pub duration: f32,
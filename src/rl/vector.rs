@@ -55,6 +55,13 @@ pub mod vector2 {
         }
     }
 
+    pub fn midpoint(v1: &Vector2, v2: &Vector2) -> Vector2 {
+        Vector2 {
+            x: (v1.x + v2.x) / 2.0,
+            y: (v1.y + v2.y) / 2.0,
+        }
+    }
+
     pub fn rotate(v: &Vector2, a: f32) -> Vector2 {
         from_angle(v.y.atan2(v.x) + a, len(v))
     }
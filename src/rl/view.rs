@@ -0,0 +1,46 @@
+use raylib_ffi::*;
+use colors::*;
+
+/// The fixed play area everything is simulated and laid out in, the window
+/// shows it scaled to fit and fills whatever is left over with bars
+pub const ARENA_WIDTH: i32 = 512;
+pub const ARENA_HEIGHT: i32 = 512;
+
+/// @brief  Where the arena lands in the window, as large as fits without stretching
+pub unsafe fn letterbox() -> Rectangle {
+    let (width, height) = (GetScreenWidth() as f32, GetScreenHeight() as f32);
+    let scale = (width / ARENA_WIDTH as f32).min(height / ARENA_HEIGHT as f32);
+    let (w, h) = (ARENA_WIDTH as f32 * scale, ARENA_HEIGHT as f32 * scale);
+    Rectangle { x: ((width - w) / 2.0).floor(), y: ((height - h) / 2.0).floor(), width: w, height: h }
+}
+
+/// @brief  Converts a point in window pixels to arena coordinates
+/// @note   A minimised window has no arena on screen, everything maps to the origin
+pub unsafe fn to_arena(point: &Vector2) -> Vector2 {
+    let view = letterbox();
+    if view.width <= 0.0 || view.height <= 0.0 {
+        return Vector2 { x: 0.0, y: 0.0 }
+    }
+    Vector2 {
+        x: (point.x - view.x) * ARENA_WIDTH as f32 / view.width,
+        y: (point.y - view.y) * ARENA_HEIGHT as f32 / view.height,
+    }
+}
+
+/// @brief  The mouse in arena coordinates, what the menus and panels expect
+pub unsafe fn mouse_position() -> Vector2 {
+    to_arena(&GetMousePosition())
+}
+
+/// @brief  Draws an arena sized render texture letterboxed into the window
+/// @note   Render textures are stored upside down, hence the negative source height
+pub unsafe fn draw_arena(texture: Texture2D) {
+    DrawTexturePro(
+        texture,
+        Rectangle { x: 0.0, y: 0.0, width: texture.width as f32, height: -texture.height as f32 },
+        letterbox(),
+        Vector2 { x: 0.0, y: 0.0 },
+        0.0,
+        WHITE,
+    );
+}
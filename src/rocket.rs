@@ -1,48 +1,107 @@
 use raylib_ffi::*;
 use colors::*;
-use crate::{rl::vector::vector2 as v, ship::Ship};
+use crate::{rl::vector::vector2 as v, ship::Ship, rules::Rules};
+use crate::rl::physics::{self, Body};
+use crate::weapon::WeaponKind;
+use crate::rl::phosphor::Stroke;
+use crate::rl::view::{ARENA_WIDTH, ARENA_HEIGHT};
 use std::f32::consts::PI;
 
+#[derive(Clone)]
 pub struct Rocket {
     pub center: Vector2,
     pub velocity: Vector2,
     pub angle: f32,
     pub life: f32,
+    pub kind: WeaponKind,
 }
 
 impl Rocket {
-    pub unsafe fn new(center: Vector2, angle: f32) -> Rocket {
+    pub unsafe fn new(center: Vector2, angle: f32, ship_velocity: Vector2, kind: WeaponKind) -> Rocket {
         Rocket { 
             center,
-            velocity: v::zero(),
+            velocity: match kind {
+                WeaponKind::Shell => v::from_angle(angle, 450.0),
+                WeaponKind::Mine => ship_velocity,
+                _ => v::zero(),
+            },
             angle,
-            life: 1.0,
+            life: match kind {
+                WeaponKind::Shell => 0.8,
+                WeaponKind::Homing => 3.0,
+                WeaponKind::Mine => 10.0,
+                _ => 1.0,
+            },
+            kind,
         }
     }
 
-    pub unsafe fn update(&mut self, main_star: &Vector2, delta: f32) {
-        let accel = 5.0;
-        self.velocity = v::add(&self.velocity, &v::from_angle(self.angle, accel));
-        self.velocity = v::add(&self.velocity, &v::gravitate(&self.center, &main_star));
-        self.center = v::add(&self.center, &v::scale(&self.velocity, delta));
-        self.center = v::wrap(&self.center, GetScreenWidth() as f32, GetScreenHeight() as f32);
+    /// @brief  Steers homing rockets toward the nearest of `targets`
+    pub unsafe fn update(&mut self, main_star: &Vector2, targets: &[Vector2], rules: &Rules, delta: f32) {
+        if self.kind == WeaponKind::Homing {
+            let nearest = targets.iter().min_by(|a, b| {
+                v::dist(&self.center, a).total_cmp(&v::dist(&self.center, b))
+            });
+            if let Some(target) = nearest {
+                let turn_rate = 2.5;
+                let desired = v::angle_from_line(&self.center, target);
+                let diff = (desired - self.angle + PI).rem_euclid(2.0 * PI) - PI;
+                self.angle += diff.clamp(-turn_rate * delta, turn_rate * delta);
+            }
+        }
+        let accel = match self.kind {
+            WeaponKind::Torpedo => 5.0 * 144.0,
+            WeaponKind::Homing => 300.0,
+            _ => 0.0,
+        };
+        let mut body = Body { position: self.center, velocity: self.velocity };
+        physics::integrate(&mut body, main_star, &v::from_angle(self.angle, accel), rules.integrator, delta);
+        if self.kind == WeaponKind::Homing {
+            // keep it slow enough to dodge
+            let max_speed = 220.0;
+            if v::len(&body.velocity) > max_speed {
+                body.velocity = v::scale(&v::norm(&body.velocity), max_speed);
+            }
+        }
+        self.velocity = body.velocity;
+        self.center = v::wrap(&body.position, ARENA_WIDTH as f32, ARENA_HEIGHT as f32);
         self.life -= delta;
     }
 
-    pub unsafe fn draw(&self) {
+    pub fn damage(&self, rules: &Rules) -> f32 {
+        rules.rocket_damage * self.kind.damage_scale()
+    }
+
+    pub unsafe fn draw(&self, stroke: Stroke) {
+        if self.kind == WeaponKind::Mine {
+            let blink = if (self.life * 4.0) as i32 % 2 == 0 { RED } else { WHITE };
+            stroke.circle(self.center, 5.0, WHITE);
+            DrawCircleV(self.center, 2.0, blink);
+            return
+        }
+        if self.kind == WeaponKind::Shell {
+            stroke.line(self.center, v::sub(&self.center, &v::scale(&self.velocity, 0.02)), WHITE);
+            return
+        }
         let angle_off = PI / 12.0;
         let length = 10.0; // half of actual
         let p0 = v::add(&self.center, &v::from_angle(self.angle + angle_off, length));
         let p1 = v::add(&self.center, &v::from_angle(self.angle - angle_off, length));
         let p2 = v::sub(&self.center, &v::from_angle(self.angle + angle_off, length));
         let p3 = v::sub(&self.center, &v::from_angle(self.angle - angle_off, length));
-        DrawLineV(p0, p1, WHITE);
-        DrawLineV(p1, p2, WHITE);
-        DrawLineV(p2, p3, WHITE);
-        DrawLineV(p3, p0, WHITE);
+        stroke.line(p0, p1, WHITE);
+        stroke.line(p1, p2, WHITE);
+        stroke.line(p2, p3, WHITE);
+        stroke.line(p3, p0, WHITE);
     }
 
     pub unsafe fn collide_with(&self, ship: &Ship) -> bool {
+        if self.kind == WeaponKind::Mine {
+            return v::dist(&self.center, &ship.center) < 30.0
+        }
+        if self.kind == WeaponKind::Shell {
+            return ship.line_hits(self.center, v::sub(&self.center, &v::scale(&self.velocity, 0.02)))
+        }
         let angle_off = PI / 12.0;
         let length = 10.0; // half of actual
         let p0 = v::add(&self.center, &v::from_angle(self.angle + angle_off, length));
@@ -0,0 +1,253 @@
+use raylib_ffi::*;
+use crate::rl::gui::{draw_label, Layout, Panel, Theme, Widget};
+use crate::rl::input::{menu_pressed, MenuKey};
+use crate::rl::physics::Integrator;
+use crate::weapon::WeaponKind;
+use crate::powerup::PowerUpKind;
+use crate::rl::view::{ARENA_WIDTH, ARENA_HEIGHT};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum FlightModel {
+    /// Input steers relative to the star and sets velocity directly
+    Arcade,
+    /// Rotation turns the ship and thrust accelerates it, momentum carries over
+    Inertial,
+}
+
+impl FlightModel {
+    pub const ALL: [FlightModel; 2] = [FlightModel::Arcade, FlightModel::Inertial];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            FlightModel::Arcade => "Arcade",
+            FlightModel::Inertial => "Inertial",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+        }
+    }
+
+    /// @brief  How quickly Rob reacts and reloads, see `Ship::skill`
+    pub fn skill(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.7,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.4,
+        }
+    }
+
+    pub fn next(&self) -> Difficulty {
+        match self {
+            Difficulty::Easy => Difficulty::Normal,
+            Difficulty::Normal => Difficulty::Hard,
+            Difficulty::Hard => Difficulty::Easy,
+        }
+    }
+}
+
+/// Match rules shared by every ship in a round
+pub struct Rules {
+    pub classic: bool,
+    pub difficulty: Difficulty,
+    pub trajectory_time: f32,
+    pub integrator: Integrator,
+    pub flight_model: FlightModel,
+    pub thrust: f32,
+    pub turn_rate: f32,
+    pub max_speed: Option<f32>,
+    pub drag: f32,
+    pub max_health: f32,
+    pub max_shield: f32,
+    pub shield_regen: f32,
+    pub shield_delay: f32,
+    pub invulnerable_time: f32,
+    pub rocket_damage: f32,
+    pub lives: u32,
+    pub weapons: Vec<WeaponKind>,
+    pub max_fuel: f32,
+    pub hyperspace_charges: u32,
+    pub powerups: Vec<PowerUpKind>,
+    pub powerup_interval: f32,
+    pub powerup_duration: f32,
+    pub max_powerups: usize,
+    pub asteroids: bool,
+    pub asteroid_count: usize,
+}
+
+impl Rules {
+    pub fn new() -> Rules {
+        Rules {
+            classic: false,
+            difficulty: Difficulty::Normal,
+            trajectory_time: 2.0,
+            integrator: Integrator::VelocityVerlet,
+            flight_model: FlightModel::Arcade,
+            thrust: 150.0,
+            turn_rate: 4.0,
+            max_speed: Some(300.0),
+            drag: 0.0,
+            max_health: 100.0,
+            max_shield: 50.0,
+            shield_regen: 10.0,
+            shield_delay: 3.0,
+            invulnerable_time: 1.5,
+            rocket_damage: 40.0,
+            lives: 3,
+            weapons: vec![
+                WeaponKind::Torpedo,
+                WeaponKind::Shell,
+                WeaponKind::Homing,
+                WeaponKind::Mine,
+                WeaponKind::Laser,
+            ],
+            max_fuel: 20.0,
+            hyperspace_charges: 3,
+            powerups: vec![
+                PowerUpKind::RapidFire,
+                PowerUpKind::Fuel,
+                PowerUpKind::Shield,
+                PowerUpKind::Hyperspace,
+                PowerUpKind::Spread,
+            ],
+            powerup_interval: 8.0,
+            powerup_duration: 8.0,
+            max_powerups: 3,
+            asteroids: false,
+            asteroid_count: 5,
+        }
+    }
+
+    /// @brief  Whether hits wear down shields and health rather than killing outright
+    pub fn damage_model(&self) -> bool {
+        !self.classic
+    }
+
+    pub fn lives(&self) -> u32 {
+        if self.classic { 1 } else { self.lives.max(1) }
+    }
+
+    /// @brief  The weapons each ship starts with, classic rules only get torpedoes
+    pub fn arsenal(&self) -> Vec<WeaponKind> {
+        if self.classic || self.weapons.is_empty() {
+            return vec![WeaponKind::Torpedo]
+        }
+        self.weapons.clone()
+    }
+
+    /// @brief  The pickups that may spawn, none under classic rules
+    pub fn power_ups(&self) -> Vec<PowerUpKind> {
+        if self.classic || self.powerup_interval <= 0.0 {
+            return vec![]
+        }
+        self.powerups.clone()
+    }
+
+    /// @brief  Whether ships may show their predicted orbit
+    pub fn allow_trajectory(&self) -> bool {
+        !self.classic && self.trajectory_time > 0.0
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum RulesField {
+    Classic,
+    FlightModel,
+    Integrator,
+    Difficulty,
+    Lives,
+    Asteroids,
+    AsteroidCount,
+    Trajectory,
+    Done,
+}
+
+/// Edits the match rules the sense switches on the menu only flip one at a time
+pub struct RulesScreen {
+    panel: Panel<RulesField>,
+}
+
+impl RulesScreen {
+    pub unsafe fn new(rules: &Rules, theme: Theme) -> RulesScreen {
+        let flight: Vec<&str> = FlightModel::ALL.iter().map(|f| f.name()).collect();
+        let integrators: Vec<&str> = Integrator::ALL.iter().map(|i| i.name()).collect();
+        let difficulties: Vec<&str> = Difficulty::ALL.iter().map(|d| d.name()).collect();
+        let index_of = |found: Option<usize>| found.unwrap_or(0);
+        let layout = Layout::vertical(4.0)
+            .with(Widget::toggle("Classic Rules", RulesField::Classic, rules.classic))
+            .with(Widget::dropdown(
+                "Flight", RulesField::FlightModel, &flight,
+                index_of(FlightModel::ALL.iter().position(|f| *f == rules.flight_model)),
+            ))
+            .with(Widget::dropdown(
+                "Integrator", RulesField::Integrator, &integrators,
+                index_of(Integrator::ALL.iter().position(|i| *i == rules.integrator)),
+            ))
+            .with(Widget::dropdown(
+                "Rob", RulesField::Difficulty, &difficulties,
+                index_of(Difficulty::ALL.iter().position(|d| *d == rules.difficulty)),
+            ))
+            .with(Widget::stepper("Lives", RulesField::Lives, rules.lives as i32, 1, 9))
+            .with_layout(Layout::horizontal(8.0)
+                .with(Widget::toggle("Asteroids", RulesField::Asteroids, rules.asteroids))
+                .with(Widget::stepper("Count", RulesField::AsteroidCount, rules.asteroid_count as i32, 1, 12)))
+            .with(Widget::slider("Trajectory", RulesField::Trajectory, rules.trajectory_time, 0.0, 5.0, 0.5))
+            .with(Widget::label("Lives are ignored under classic rules"))
+            .with(Widget::label("A trajectory of 0 hides the preview"))
+            .with(Widget::button("Done", RulesField::Done));
+        let width = ARENA_WIDTH as f32;
+        let area = Rectangle { x: 40.0, y: 90.0, width: width - 80.0, height: ARENA_HEIGHT as f32 - 120.0 };
+        RulesScreen { panel: Panel::new(layout, area, theme) }
+    }
+
+    /// @brief  Applies every change straight to the rules, returns true when done
+    pub unsafe fn update(&mut self, rules: &mut Rules, cursor: &Vector2) -> bool {
+        let capturing = self.panel.capturing();
+        match self.panel.update(cursor) {
+            Some(RulesField::Classic) => rules.classic = self.panel.get_bool(RulesField::Classic).unwrap_or(rules.classic),
+            Some(RulesField::FlightModel) => {
+                let i = self.panel.get_index(RulesField::FlightModel).unwrap_or(0);
+                rules.flight_model = FlightModel::ALL[i];
+            },
+            Some(RulesField::Integrator) => {
+                let i = self.panel.get_index(RulesField::Integrator).unwrap_or(0);
+                rules.integrator = Integrator::ALL[i];
+            },
+            Some(RulesField::Difficulty) => {
+                let i = self.panel.get_index(RulesField::Difficulty).unwrap_or(0);
+                rules.difficulty = Difficulty::ALL[i];
+            },
+            Some(RulesField::Lives) => rules.lives = self.panel.get_i32(RulesField::Lives).unwrap_or(3) as u32,
+            Some(RulesField::Asteroids) => rules.asteroids = self.panel.get_bool(RulesField::Asteroids).unwrap_or(false),
+            Some(RulesField::AsteroidCount) => {
+                rules.asteroid_count = self.panel.get_i32(RulesField::AsteroidCount).unwrap_or(5) as usize;
+            },
+            Some(RulesField::Trajectory) => {
+                rules.trajectory_time = self.panel.get_f32(RulesField::Trajectory).unwrap_or(2.0);
+            },
+            Some(RulesField::Done) => return true,
+            None => {},
+        }
+        !capturing && menu_pressed(MenuKey::Back)
+    }
+
+    pub unsafe fn draw(&self) {
+        draw_label("Rules", 40, ARENA_WIDTH / 2, 50, self.panel.theme.color0);
+        self.panel.draw();
+    }
+}
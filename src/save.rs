@@ -0,0 +1,259 @@
+use crate::rl::config::Config;
+use crate::rl::gui::{draw_label, fmt_time, Theme};
+use crate::rules::Difficulty;
+use crate::ship::Ship;
+use crate::survival::Survival;
+use std::env;
+use std::path::{Path, PathBuf};
+
+/// Bumped whenever the layout of the save file changes
+pub const SAVE_VERSION: u32 = 1;
+
+/// @brief  Where save data lives, following each platform's convention for user data
+pub fn data_dir() -> PathBuf {
+    let home = env::var("HOME").map(PathBuf::from).unwrap_or_else(|_| PathBuf::from("."));
+    let base = if cfg!(target_os = "windows") {
+        env::var("APPDATA").map(PathBuf::from).unwrap_or(home)
+    } else if cfg!(target_os = "macos") {
+        home.join("Library").join("Application Support")
+    } else {
+        env::var("XDG_DATA_HOME").map(PathBuf::from).unwrap_or_else(|_| home.join(".local").join("share"))
+    };
+    base.join("spacewar")
+}
+
+#[derive(Clone)]
+pub struct Stats {
+    pub name: String,
+    pub wins_vs_rob: [u32; 3],
+    pub losses_vs_rob: [u32; 3],
+    pub survival_best: u32,
+    pub survival_longest: f32,
+    pub shots_fired: u32,
+    pub shots_hit: u32,
+    pub deaths_star: u32,
+    pub deaths_weapon: u32,
+    pub deaths_collision: u32,
+    pub play_time: f32,
+}
+
+impl Stats {
+    pub fn new(name: &str) -> Stats {
+        Stats {
+            name: name.to_string(),
+            wins_vs_rob: [0; 3],
+            losses_vs_rob: [0; 3],
+            survival_best: 0,
+            survival_longest: 0.0,
+            shots_fired: 0,
+            shots_hit: 0,
+            deaths_star: 0,
+            deaths_weapon: 0,
+            deaths_collision: 0,
+            play_time: 0.0,
+        }
+    }
+
+    fn read(config: &Config, section: &str) -> Option<Stats> {
+        let mut stats = Stats::new(&config.get_str(section, "name")?);
+        for (i, difficulty) in Difficulty::ALL.iter().enumerate() {
+            let key = difficulty.name().to_lowercase();
+            stats.wins_vs_rob[i] = config.get_or(section, &format!("wins_{}", key), 0);
+            stats.losses_vs_rob[i] = config.get_or(section, &format!("losses_{}", key), 0);
+        }
+        stats.survival_best = config.get_or(section, "survival_best", 0);
+        stats.survival_longest = config.get_or(section, "survival_longest", 0.0);
+        stats.shots_fired = config.get_or(section, "shots_fired", 0);
+        stats.shots_hit = config.get_or(section, "shots_hit", 0);
+        stats.deaths_star = config.get_or(section, "deaths_star", 0);
+        stats.deaths_weapon = config.get_or(section, "deaths_weapon", 0);
+        stats.deaths_collision = config.get_or(section, "deaths_collision", 0);
+        stats.play_time = config.get_or(section, "play_time", 0.0);
+        Some(stats)
+    }
+
+    fn write(&self, config: &mut Config, section: &str) {
+        config.set_str(section, "name", &self.name);
+        for (i, difficulty) in Difficulty::ALL.iter().enumerate() {
+            let key = difficulty.name().to_lowercase();
+            config.set(section, &format!("wins_{}", key), self.wins_vs_rob[i]);
+            config.set(section, &format!("losses_{}", key), self.losses_vs_rob[i]);
+        }
+        config.set(section, "survival_best", self.survival_best);
+        config.set(section, "survival_longest", self.survival_longest);
+        config.set(section, "shots_fired", self.shots_fired);
+        config.set(section, "shots_hit", self.shots_hit);
+        config.set(section, "deaths_star", self.deaths_star);
+        config.set(section, "deaths_weapon", self.deaths_weapon);
+        config.set(section, "deaths_collision", self.deaths_collision);
+        config.set(section, "play_time", self.play_time);
+    }
+
+    /// @brief  Adds a ship's shots and deaths from the round that just ended
+    pub fn record_ship(&mut self, ship: &Ship, time: f32) {
+        self.shots_fired += ship.shots_fired;
+        self.shots_hit += ship.shots_hit;
+        self.deaths_star += ship.deaths_star;
+        self.deaths_weapon += ship.deaths_weapon;
+        self.deaths_collision += ship.deaths_collision;
+        self.play_time += time;
+    }
+
+    pub fn record_rob(&mut self, difficulty: Difficulty, won: bool) {
+        let i = Difficulty::ALL.iter().position(|d| *d == difficulty).unwrap_or(1);
+        if won {
+            self.wins_vs_rob[i] += 1;
+        } else {
+            self.losses_vs_rob[i] += 1;
+        }
+    }
+
+    pub fn record_survival(&mut self, survival: &Survival) {
+        self.survival_best = self.survival_best.max(survival.score());
+        self.survival_longest = self.survival_longest.max(survival.time);
+    }
+
+    pub fn accuracy(&self) -> f32 {
+        if self.shots_fired == 0 { return 0.0 }
+        self.shots_hit as f32 / self.shots_fired as f32 * 100.0
+    }
+
+    pub unsafe fn draw(&self, x: i32, y: i32, theme: &Theme) {
+        draw_label(&self.name, 40, x, y, theme.color0);
+        let mut lines: Vec<String> = vec![];
+        for (i, difficulty) in Difficulty::ALL.iter().enumerate() {
+            lines.push(format!("vs Rob ({}): {} - {}", difficulty.name(), self.wins_vs_rob[i], self.losses_vs_rob[i]));
+        }
+        lines.push(format!("Survival best: {} ({})", self.survival_best, fmt_time(self.survival_longest)));
+        lines.push(format!("Accuracy: {:.0}% ({} / {})", self.accuracy(), self.shots_hit, self.shots_fired));
+        lines.push(format!(
+            "Deaths: {} star, {} weapons, {} crashes",
+            self.deaths_star, self.deaths_weapon, self.deaths_collision
+        ));
+        lines.push(format!("Play time: {}", fmt_time(self.play_time)));
+        for (i, line) in lines.iter().enumerate() {
+            draw_label(line, 20, x, y + 50 + i as i32 * 26, theme.muted);
+        }
+    }
+}
+
+/// Every profile's statistics, read from and written back to `stats.toml`
+pub struct SaveData {
+    pub profiles: Vec<Stats>,
+    path: PathBuf,
+    /// the file on disk had nothing usable, so saving must not make it the backup
+    damaged: bool,
+}
+
+/// @brief  Every profile in a stats file, the first of any repeated name wins
+/// @note   A missing `version` reads as the current one and files from a newer
+///         build may hold fields we don't know, either way read what we can
+fn read_profiles(config: &Config) -> Vec<Stats> {
+    let mut profiles: Vec<Stats> = vec![];
+    for section in config.sections() {
+        if !section.starts_with("profile.") { continue }
+        if let Some(stats) = Stats::read(config, &section) {
+            if !profiles.iter().any(|p| p.name == stats.name) {
+                profiles.push(stats);
+            }
+        }
+    }
+    profiles
+}
+
+impl SaveData {
+    pub fn load() -> SaveData {
+        SaveData::load_from(&data_dir().join("stats.toml"))
+    }
+
+    /// @brief  Reads the stats file, or its backup when the file holds no profiles
+    pub fn load_from(path: &Path) -> SaveData {
+        let mut save = SaveData { profiles: vec![], path: path.to_path_buf(), damaged: false };
+        let config = match Config::load(path) {
+            Some(config) => config,
+            None => return save,
+        };
+        save.profiles = read_profiles(&config);
+        if save.profiles.is_empty() && !config.sections().is_empty() {
+            save.damaged = true;
+            if let Some(backup) = Config::load_backup(path) {
+                save.profiles = read_profiles(&backup);
+            }
+        }
+        save
+    }
+
+    pub fn save(&self) {
+        let mut config = Config::new();
+        config.set("", "version", SAVE_VERSION);
+        for (i, stats) in self.profiles.iter().enumerate() {
+            stats.write(&mut config, &format!("profile.{}", i));
+        }
+        let saved = match self.damaged {
+            true => config.save_keeping_backup(&self.path),
+            false => config.save(&self.path),
+        };
+        if let Err(e) = saved {
+            eprintln!("could not save statistics to {}: {}", self.path.display(), e);
+        }
+    }
+
+    /// @brief  The statistics for a profile, created empty on first use
+    pub fn profile(&mut self, name: &str) -> &mut Stats {
+        let i = match self.profiles.iter().position(|p| p.name == name) {
+            Some(i) => i,
+            None => {
+                self.profiles.push(Stats::new(name));
+                self.profiles.len() - 1
+            },
+        };
+        &mut self.profiles[i]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rl::config::Scratch;
+    use std::fs;
+
+    fn saved(names: &[&str]) -> String {
+        let mut config = Config::new();
+        config.set("", "version", SAVE_VERSION);
+        for (i, name) in names.iter().enumerate() {
+            Stats::new(name).write(&mut config, &format!("profile.{}", i));
+        }
+        config.serialize()
+    }
+
+    #[test]
+    fn missing_version_still_reads_profiles() {
+        let scratch = Scratch::new("save-version");
+        let path = scratch.file("stats.toml");
+        fs::write(&path, saved(&["Ada", "Bob"]).replace("version = 1\n", "")).unwrap();
+        let names: Vec<String> = SaveData::load_from(&path).profiles.into_iter().map(|p| p.name).collect();
+        assert_eq!(names, vec!["Ada".to_string(), "Bob".to_string()]);
+    }
+
+    #[test]
+    fn damaged_file_loads_the_backup_and_keeps_it() {
+        let scratch = Scratch::new("save-damaged");
+        let path = scratch.file("stats.toml");
+        fs::write(&path, "version = 1\n[profile.0]\nnme = broken\n").unwrap();
+        fs::write(path.with_extension("bak"), saved(&["Ada"])).unwrap();
+        let save = SaveData::load_from(&path);
+        assert_eq!(save.profiles.len(), 1);
+        assert_eq!(save.profiles[0].name, "Ada");
+        save.save();
+        assert_eq!(fs::read_to_string(path.with_extension("bak")).unwrap(), saved(&["Ada"]));
+        assert_eq!(SaveData::load_from(&path).profiles[0].name, "Ada");
+    }
+
+    #[test]
+    fn duplicate_names_keep_the_first() {
+        let scratch = Scratch::new("save-duplicate");
+        let path = scratch.file("stats.toml");
+        fs::write(&path, saved(&["Ada", "Ada"])).unwrap();
+        assert_eq!(SaveData::load_from(&path).profiles.len(), 1);
+    }
+}
@@ -0,0 +1,370 @@
+use raylib_ffi::*;
+use crate::rl::config::Config;
+use crate::rl::gui::{draw_label, draw_text, Layout, Panel, TextPosition, Theme, Widget};
+use crate::rl::input::{menu_pressed, MenuKey};
+use crate::save::data_dir;
+use crate::rl::view::{ARENA_WIDTH, ARENA_HEIGHT};
+use std::path::PathBuf;
+
+/// Window sizes offered on the settings screen
+pub const RESOLUTIONS: [(i32, i32); 6] = [(512, 512), (768, 768), (1024, 768), (1280, 720), (1600, 900), (1920, 1080)];
+
+/// Frame rate caps offered on the settings screen, 0 leaves it unlimited
+pub const FPS_CAPS: [i32; 6] = [30, 60, 120, 144, 240, 0];
+
+/// Display and audio options, read from settings.toml and then the command line
+#[derive(Clone, PartialEq)]
+pub struct Settings {
+    pub width: i32,
+    pub height: i32,
+    pub fullscreen: bool,
+    pub vsync: bool,
+    pub fps_cap: i32,
+    pub shader_intensity: f32,
+    pub show_fps: bool,
+    /// read and saved for when the game makes sound, until then there's no slider for it
+    pub volume: f32,
+    pub vector_display: bool,
+    pub persistence: f32,
+    pub reduce_motion: bool,
+    /// kept for an arena bigger than the window, with none yet there's no toggle for it
+    pub auto_zoom: bool,
+    pub star_density: f32,
+    pub star_brightness: f32,
+    /// the catalogue sky in place of the generated one
+    pub planetarium: bool,
+    /// name of the `Theme` the menus are drawn in
+    pub theme: String,
+    path: PathBuf,
+    /// what settings.toml held, flags from the command line, and every value as
+    /// the session started, so a flag nobody touched isn't written back
+    file: Config,
+    overrides: Config,
+    launched: Config,
+}
+
+impl Settings {
+    pub fn new() -> Settings {
+        Settings {
+            width: 512,
+            height: 512,
+            fullscreen: false,
+            vsync: false,
+            fps_cap: 144,
+            shader_intensity: 1.0,
+            show_fps: true,
+            volume: 1.0,
+            vector_display: false,
+            persistence: 0.4,
+            reduce_motion: false,
+            auto_zoom: false,
+            star_density: 1.0,
+            star_brightness: 1.0,
+            planetarium: false,
+            theme: Theme::CLASSIC.name.to_string(),
+            path: data_dir().join("settings.toml"),
+            file: Config::new(),
+            overrides: Config::new(),
+            launched: Config::new(),
+        }
+    }
+
+    /// @brief  The saved settings with any `--key value` flags laid over the top
+    /// @note   Overrides only last for the session unless the settings screen changes them
+    pub fn load(args: &[String]) -> Settings {
+        let path = data_dir().join("settings.toml");
+        Settings::layered(Config::load(&path).unwrap_or_default(), parse_args(args))
+    }
+
+    fn layered(file: Config, overrides: Config) -> Settings {
+        let mut settings = Settings::new();
+        settings.read(&file);
+        settings.read(&overrides);
+        settings.launched = settings.to_config();
+        settings.file = file;
+        settings.overrides = overrides;
+        settings
+    }
+
+    fn read(&mut self, config: &Config) {
+        self.width = config.get_or("", "width", self.width).max(320);
+        self.height = config.get_or("", "height", self.height).max(240);
+        self.fullscreen = config.get_or("", "fullscreen", self.fullscreen);
+        self.vsync = config.get_or("", "vsync", self.vsync);
+        self.fps_cap = config.get_or("", "fps_cap", self.fps_cap).max(0);
+        self.shader_intensity = config.get_or("", "shader_intensity", self.shader_intensity).clamp(0.0, 1.0);
+        self.show_fps = config.get_or("", "show_fps", self.show_fps);
+        self.volume = config.get_or("", "volume", self.volume).clamp(0.0, 1.0);
+        self.vector_display = config.get_or("", "vector_display", self.vector_display);
+        self.persistence = config.get_or("", "persistence", self.persistence).clamp(0.1, 2.0);
+        self.reduce_motion = config.get_or("", "reduce_motion", self.reduce_motion);
+        self.auto_zoom = config.get_or("", "auto_zoom", self.auto_zoom);
+        self.star_density = config.get_or("", "star_density", self.star_density).clamp(0.0, 2.0);
+        self.star_brightness = config.get_or("", "star_brightness", self.star_brightness).clamp(0.0, 1.0);
+        self.planetarium = config.get_or("", "planetarium", self.planetarium);
+        if let Some(theme) = config.get_str("", "theme") {
+            self.theme = Theme::named(&theme).name.to_string();
+        }
+    }
+
+    /// @brief  Writes the settings, leaving the file's own value for any flag
+    ///         that hasn't been changed since the game started
+    pub fn save(&mut self) {
+        let config = self.saved_config();
+        match config.save(&self.path) {
+            Ok(()) => self.file = config,
+            Err(e) => eprintln!("could not save settings to {}: {}", self.path.display(), e),
+        }
+    }
+
+    fn saved_config(&self) -> Config {
+        let mut config = self.to_config();
+        for key in self.overrides.keys("") {
+            if config.get::<String>("", &key) != self.launched.get::<String>("", &key) { continue }
+            match self.file.get::<String>("", &key) {
+                Some(value) => config.set("", &key, value),
+                None => config.remove("", &key),
+            }
+        }
+        config
+    }
+
+    fn to_config(&self) -> Config {
+        let mut config = Config::new();
+        config.set("", "width", self.width);
+        config.set("", "height", self.height);
+        config.set("", "fullscreen", self.fullscreen);
+        config.set("", "vsync", self.vsync);
+        config.set("", "fps_cap", self.fps_cap);
+        config.set("", "shader_intensity", self.shader_intensity);
+        config.set("", "show_fps", self.show_fps);
+        config.set("", "volume", self.volume);
+        config.set("", "vector_display", self.vector_display);
+        config.set("", "persistence", self.persistence);
+        config.set("", "reduce_motion", self.reduce_motion);
+        config.set("", "auto_zoom", self.auto_zoom);
+        config.set("", "star_density", self.star_density);
+        config.set("", "star_brightness", self.star_brightness);
+        config.set("", "planetarium", self.planetarium);
+        config.set_str("", "theme", &self.theme);
+        config
+    }
+
+    pub fn theme(&self) -> Theme {
+        Theme::named(&self.theme)
+    }
+
+    /// @brief  Window flags that have to be set before the window is created
+    pub fn config_flags(&self) -> u32 {
+        let mut flags = enums::ConfigFlags::WindowResizable as u32;
+        if self.fullscreen { flags |= enums::ConfigFlags::FullscreenMode as u32 }
+        if self.vsync { flags |= enums::ConfigFlags::VsyncHint as u32 }
+        flags
+    }
+
+    /// @brief  Brings the open window in line with the settings, and the audio device once there is one
+    pub unsafe fn apply(&self) {
+        if GetScreenWidth() != self.width || GetScreenHeight() != self.height {
+            SetWindowSize(self.width, self.height);
+        }
+        if IsWindowFullscreen() != self.fullscreen {
+            ToggleFullscreen();
+        }
+        let vsync = enums::ConfigFlags::VsyncHint as u32;
+        match self.vsync {
+            true => SetWindowState(vsync),
+            false => ClearWindowState(vsync),
+        }
+        SetTargetFPS(self.fps_cap);
+        if IsAudioDeviceReady() {
+            SetMasterVolume(self.volume);
+        }
+    }
+}
+
+/// @brief  Turns `--key value`, `--key=value`, `--flag` and `--no-flag` into the
+///         same keys settings.toml uses, dashes in a key read as underscores
+fn parse_args(args: &[String]) -> Config {
+    let mut config = Config::new();
+    let mut args = args.iter().peekable();
+    while let Some(arg) = args.next() {
+        let flag = match arg.strip_prefix("--") {
+            Some(flag) => flag.replace('-', "_"),
+            None => {
+                eprintln!("ignoring argument {}", arg);
+                continue
+            },
+        };
+        if let Some((key, value)) = flag.split_once('=') {
+            config.set("", key, value);
+        } else if let Some(value) = args.next_if(|next| !next.starts_with("--")) {
+            config.set("", &flag, value);
+        } else if let Some(key) = flag.strip_prefix("no_") {
+            config.set("", key, false);
+        } else {
+            config.set("", &flag, true);
+        }
+    }
+    config
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum SettingsField {
+    Resolution,
+    Fullscreen,
+    VSync,
+    FpsCap,
+    Theme,
+    Shader,
+    ShowFps,
+    VectorDisplay,
+    Persistence,
+    ReduceMotion,
+    Planetarium,
+    StarDensity,
+    StarBrightness,
+    Done,
+}
+
+/// Edits the display and audio settings, saving whenever one changes
+pub struct SettingsScreen {
+    panel: Panel<SettingsField>,
+    resolutions: Vec<(i32, i32)>,
+}
+
+impl SettingsScreen {
+    pub unsafe fn new(settings: &Settings) -> SettingsScreen {
+        // a size set from the command line or by hand still gets a place in the list
+        let mut resolutions = RESOLUTIONS.to_vec();
+        if !resolutions.contains(&(settings.width, settings.height)) {
+            resolutions.push((settings.width, settings.height));
+        }
+        let names: Vec<String> = resolutions.iter().map(|(w, h)| format!("{}x{}", w, h)).collect();
+        let names: Vec<&str> = names.iter().map(|n| n.as_str()).collect();
+        let caps: Vec<String> = FPS_CAPS.iter()
+            .map(|fps| if *fps == 0 { "Unlimited".to_string() } else { fps.to_string() })
+            .collect();
+        let caps: Vec<&str> = caps.iter().map(|c| c.as_str()).collect();
+        let resolution = resolutions.iter().position(|r| *r == (settings.width, settings.height)).unwrap_or(0);
+        let cap = FPS_CAPS.iter().position(|fps| *fps == settings.fps_cap).unwrap_or(FPS_CAPS.len() - 1);
+        let themes: Vec<&str> = Theme::ALL.iter().map(|t| t.name).collect();
+        let theme = Theme::ALL.iter().position(|t| t.name == settings.theme).unwrap_or(0);
+        let layout = Layout::vertical(4.0)
+            .with(Widget::dropdown("Resolution", SettingsField::Resolution, &names, resolution))
+            .with(Widget::toggle("Fullscreen", SettingsField::Fullscreen, settings.fullscreen))
+            .with(Widget::dropdown("FPS Cap", SettingsField::FpsCap, &caps, cap))
+            .with(Widget::dropdown("Theme", SettingsField::Theme, &themes, theme))
+            .with_layout(Layout::horizontal(8.0)
+                .with(Widget::toggle("VSync", SettingsField::VSync, settings.vsync))
+                .with(Widget::toggle("Show FPS", SettingsField::ShowFps, settings.show_fps)))
+            .with(Widget::slider("CRT", SettingsField::Shader, settings.shader_intensity, 0.0, 1.0, 0.1))
+            .with(Widget::toggle("Vector Display", SettingsField::VectorDisplay, settings.vector_display))
+            .with(Widget::slider("Afterglow", SettingsField::Persistence, settings.persistence, 0.1, 2.0, 0.1))
+            .with(Widget::toggle("Reduce Motion", SettingsField::ReduceMotion, settings.reduce_motion))
+            .with(Widget::toggle("Real Sky", SettingsField::Planetarium, settings.planetarium))
+            .with_layout(Layout::horizontal(8.0)
+                .with(Widget::slider("Stars", SettingsField::StarDensity, settings.star_density, 0.0, 2.0, 0.1))
+                .with(Widget::slider("Shine", SettingsField::StarBrightness, settings.star_brightness, 0.0, 1.0, 0.1)))
+            .with(Widget::button("Done", SettingsField::Done));
+        let width = ARENA_WIDTH as f32;
+        // twelve rows only just fit between the title and the hint
+        let area = Rectangle { x: 40.0, y: 80.0, width: width - 80.0, height: ARENA_HEIGHT as f32 - 100.0 };
+        SettingsScreen { panel: Panel::new(layout, area, settings.theme()), resolutions }
+    }
+
+    /// @brief  Applies and saves every change straight away, returns true when done
+    pub unsafe fn update(&mut self, settings: &mut Settings, cursor: &Vector2) -> bool {
+        let capturing = self.panel.capturing();
+        let before = settings.clone();
+        match self.panel.update(cursor) {
+            Some(SettingsField::Resolution) => {
+                let i = self.panel.get_index(SettingsField::Resolution).unwrap_or(0);
+                (settings.width, settings.height) = self.resolutions[i];
+            },
+            Some(SettingsField::Fullscreen) => {
+                settings.fullscreen = self.panel.get_bool(SettingsField::Fullscreen).unwrap_or(settings.fullscreen);
+            },
+            Some(SettingsField::VSync) => settings.vsync = self.panel.get_bool(SettingsField::VSync).unwrap_or(settings.vsync),
+            Some(SettingsField::FpsCap) => {
+                let i = self.panel.get_index(SettingsField::FpsCap).unwrap_or(0);
+                settings.fps_cap = FPS_CAPS[i];
+            },
+            Some(SettingsField::Theme) => {
+                let i = self.panel.get_index(SettingsField::Theme).unwrap_or(0);
+                settings.theme = Theme::ALL[i].name.to_string();
+                self.panel.theme = Theme::ALL[i];
+            },
+            Some(SettingsField::Shader) => {
+                settings.shader_intensity = self.panel.get_f32(SettingsField::Shader).unwrap_or(settings.shader_intensity);
+            },
+            Some(SettingsField::ShowFps) => settings.show_fps = self.panel.get_bool(SettingsField::ShowFps).unwrap_or(true),
+            Some(SettingsField::VectorDisplay) => {
+                settings.vector_display = self.panel.get_bool(SettingsField::VectorDisplay).unwrap_or(false);
+            },
+            Some(SettingsField::Persistence) => {
+                settings.persistence = self.panel.get_f32(SettingsField::Persistence).unwrap_or(settings.persistence);
+            },
+            Some(SettingsField::ReduceMotion) => {
+                settings.reduce_motion = self.panel.get_bool(SettingsField::ReduceMotion).unwrap_or(false);
+            },
+            Some(SettingsField::Planetarium) => {
+                settings.planetarium = self.panel.get_bool(SettingsField::Planetarium).unwrap_or(false);
+            },
+            Some(SettingsField::StarDensity) => {
+                settings.star_density = self.panel.get_f32(SettingsField::StarDensity).unwrap_or(settings.star_density);
+            },
+            Some(SettingsField::StarBrightness) => {
+                settings.star_brightness = self.panel.get_f32(SettingsField::StarBrightness).unwrap_or(settings.star_brightness);
+            },
+            Some(SettingsField::Done) => return true,
+            None => {},
+        }
+        if *settings != before {
+            settings.apply();
+            settings.save();
+        }
+        !capturing && menu_pressed(MenuKey::Back)
+    }
+
+    pub unsafe fn draw(&self) {
+        draw_label("Settings", 40, ARENA_WIDTH / 2, 50, self.panel.theme.color0);
+        self.panel.draw();
+        draw_text("Flags like --fps-cap 60 override for one run".to_string(), TextPosition::BL, 1, 20, self.panel.theme.muted);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layered(file: &str, args: &[&str]) -> Settings {
+        let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+        Settings::layered(Config::parse(file), parse_args(&args))
+    }
+
+    #[test]
+    fn flags_override_the_file() {
+        let settings = layered("fps_cap = 60\nvsync = true\n", &["--fps-cap", "30", "--no-vsync", "--show-fps=false"]);
+        assert_eq!(settings.fps_cap, 30);
+        assert!(!settings.vsync);
+        assert!(!settings.show_fps);
+    }
+
+    #[test]
+    fn untouched_flags_are_not_saved() {
+        let settings = layered("fps_cap = 60\n", &["--fps-cap", "30", "--fullscreen"]);
+        let saved = settings.saved_config();
+        assert_eq!(saved.get::<i32>("", "fps_cap"), Some(60));
+        assert_eq!(saved.get::<bool>("", "fullscreen"), None);
+    }
+
+    #[test]
+    fn flags_changed_on_screen_are_saved() {
+        let mut settings = layered("fps_cap = 60\n", &["--fps-cap", "30"]);
+        settings.fps_cap = 120;
+        settings.volume = 0.5;
+        let saved = settings.saved_config();
+        assert_eq!(saved.get::<i32>("", "fps_cap"), Some(120));
+        assert_eq!(saved.get::<f32>("", "volume"), Some(0.5));
+    }
+}
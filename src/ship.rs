@@ -1,22 +1,56 @@
 use raylib_ffi::*;
 use colors::*;
 use crate::rl::timer::Timer;
+use crate::rl::gui::{draw_bar, draw_text, TextPosition};
 use crate::rl::vector::vector2 as v;
-use crate::get_input;
-use crate::key;
+use crate::rl::input::{Action, Bindings, InputMap};
 use crate::rocket::Rocket;
+use crate::weapon::{Weapon, WeaponKind};
+use crate::powerup::PowerUpKind;
+use crate::profile::Profile;
+use crate::rules::{Rules, FlightModel};
+use crate::rl::physics::{self, Body};
+use crate::rl::phosphor::Stroke;
+use crate::rl::view::{ARENA_WIDTH, ARENA_HEIGHT};
 use std::f32::consts::PI;
 use rand::Rng;
 use core::ptr::null_mut;
 
+/// Rob heads straight out once the star is this close
+const ROB_STAR_CLEARANCE: f32 = 100.0;
+/// Under the inertial model Rob stops turning within this angle of where it wants
+/// to point, and only burns within the wider one
+const ROB_ALIGNED: f32 = 0.1;
+const ROB_THRUST_CONE: f32 = 0.35;
+/// Closing speed Rob stops burning at when chasing, so the tank lasts the fight
+const ROB_CRUISE: f32 = 80.0;
+
+#[derive(Clone, Copy)]
 pub enum Player {
     One,
     Two,
     Rob,
 }
 
+/// Something that happened to a ship worth showing, collected until the caller drains them
+#[derive(Clone, Copy)]
+pub enum ShipEvent {
+    /// a life was lost, where the ship was and how fast it was going
+    Exploded { center: Vector2, velocity: Vector2 },
+    /// the last life went, the hull breaks apart
+    Destroyed { center: Vector2, velocity: Vector2, color: Color },
+    /// one of its rockets ran out of fuel
+    RocketExpired(Vector2),
+    /// one of its rockets struck something
+    RocketHit(Vector2),
+}
+
+#[derive(Clone)]
 pub struct Ship {
     pub player: Player,
+    pub name: String,
+    pub color: Color,
+    pub controls: Bindings,
     pub center: Vector2,
     velocity: Vector2,
     move_angle: f32,
@@ -24,24 +58,63 @@ pub struct Ship {
     view_mode: bool,
     pub rockets: Vec<Rocket>,
     rob_timer: Timer,
-    rocket_cooldown: Timer,
+    pub weapons: Vec<Weapon>,
+    pub weapon: usize,
+    pub beam: Option<(Vector2, Vector2)>,
+    /// the current laser burst has already counted as a hit
+    beam_hit: bool,
     input_vector: Vector2,
     pub dead: bool,
+    pub health: f32,
+    pub shield: f32,
+    pub lives: u32,
+    shield_timer: Timer,
+    invulnerable: Timer,
+    pub fuel: f32,
+    pub hyperspace: u32,
+    rapid_fire: Timer,
+    shield_boost: Timer,
+    spread: Timer,
+    pub thrusting: bool,
+    pub show_trajectory: bool,
+    trajectory: Vec<Vector2>,
+    pub skill: f32,
+    pub shots_fired: u32,
+    pub shots_hit: u32,
+    pub deaths_star: u32,
+    pub deaths_weapon: u32,
+    pub deaths_collision: u32,
+    pub events: Vec<ShipEvent>,
 }
 
 impl Ship {
-    pub unsafe fn new(player: Player) -> Ship {
+    pub unsafe fn new(player: Player, rules: &Rules) -> Ship {
         Ship {
             center: match player {
                 Player::One => Vector2 { 
-                    x: (GetScreenWidth() / 2 - 56) as f32, 
-                    y: (GetScreenHeight() / 2 - 56) as f32
+                    x: (ARENA_WIDTH / 2 - 56) as f32, 
+                    y: (ARENA_HEIGHT / 2 - 56) as f32
                 },
                 _ => Vector2 {
-                    x: (GetScreenWidth() / 2 + 56) as f32, 
-                    y: (GetScreenHeight() / 2 + 56) as f32
+                    x: (ARENA_WIDTH / 2 + 56) as f32, 
+                    y: (ARENA_HEIGHT / 2 + 56) as f32
                 },
             },
+            name: match player {
+                Player::One => "Player 1",
+                Player::Two => "Player 2",
+                Player::Rob => "Rob",
+            }.to_string(),
+            color: match player {
+                Player::One => GREEN,
+                Player::Two => GOLD,
+                Player::Rob => RED,
+            },
+            controls: match player {
+                Player::One => InputMap::defaults()[0],
+                Player::Two => InputMap::defaults()[1],
+                Player::Rob => Bindings::new(&[]),
+            },
             player,
             velocity: v::zero(),
             move_angle: 0.0,
@@ -49,14 +122,76 @@ impl Ship {
             view_mode: true,
             rockets: vec![],
             rob_timer: Timer::new(0.0),
-            rocket_cooldown: Timer::new(0.5),
+            weapons: rules.arsenal().into_iter().map(Weapon::new).collect(),
+            weapon: 0,
+            beam: None,
+            beam_hit: false,
             input_vector: v::zero(),
             dead: false,
+            health: rules.max_health,
+            shield: rules.max_shield,
+            lives: rules.lives(),
+            shield_timer: Timer::new(rules.shield_delay),
+            invulnerable: Timer::new(rules.invulnerable_time),
+            fuel: rules.max_fuel,
+            hyperspace: rules.hyperspace_charges,
+            rapid_fire: Timer::new(rules.powerup_duration),
+            shield_boost: Timer::new(rules.powerup_duration),
+            spread: Timer::new(rules.powerup_duration),
+            thrusting: false,
+            show_trajectory: false,
+            trajectory: vec![],
+            skill: 1.0,
+            shots_fired: 0,
+            shots_hit: 0,
+            deaths_star: 0,
+            deaths_weapon: 0,
+            deaths_collision: 0,
+            events: vec![],
+        }
+    }
+
+    /// @brief  Where exhaust leaves the hull, which way it goes and the ship's
+    ///         velocity to carry along, while the engine is firing
+    pub fn exhaust(&self) -> Option<(Vector2, f32, Vector2)> {
+        if !self.thrusting || self.dead {
+            return None
+        }
+        let rear = v::sub(&self.center, &v::from_angle(self.view_angle, 8.0));
+        Some((rear, self.view_angle + PI, self.velocity))
+    }
+
+    /// @brief  Flies the ship under a profile's name and hull colour
+    pub fn set_profile(&mut self, profile: &Profile) {
+        self.name = profile.name.clone();
+        self.color = profile.color;
+    }
+
+    /// @brief  A single-life Rob for survival waves, higher skill reacts and reloads faster
+    pub unsafe fn new_bot(rules: &Rules, center: Vector2, skill: f32) -> Ship {
+        Ship {
+            center,
+            lives: 1,
+            skill,
+            ..Ship::new(Player::Rob, rules)
         }
     }
 
+    /// @brief  Tops up fuel, ammo, jumps, shields and hull between survival waves
+    pub fn replenish(&mut self, rules: &Rules) {
+        self.weapons = rules.arsenal().into_iter().map(Weapon::new).collect();
+        self.weapon = 0;
+        self.fuel = rules.max_fuel;
+        self.hyperspace = rules.hyperspace_charges;
+        self.health = rules.max_health;
+        self.shield = rules.max_shield;
+    }
+
     pub unsafe fn rob_input(&mut self, main_star: &Vector2) -> Vector2 {
-        self.rob_timer.start_override(rand::thread_rng().gen_range(0.5..3.0));
+        self.rob_timer.start_override(rand::thread_rng().gen_range(0.5..3.0) / self.skill);
+        if rand::thread_rng().gen_bool(0.3) {
+            self.next_weapon();
+        }
         if v::dist(&self.center, main_star) <= 100.0 && self.input_vector.y >= 0.0 {
             return Vector2 {
                 x: if self.input_vector.x == 1.0 { -1.0 } else { 1.0 },
@@ -69,34 +204,81 @@ impl Ship {
         }
     }
 
-    pub unsafe fn update(&mut self, main_star: &Vector2, other: &Ship, delta: f32) {
+    /// @brief  Rob's steering under the inertial model, turns to a bearing and only
+    ///         thrusts once facing it, away from the star when close or else at the nearest enemy
+    fn rob_inertial(&self, main_star: &Vector2, enemies: &[Vector2]) -> Vector2 {
+        let target = enemies.iter().min_by(|a, b| v::dist(&self.center, a).total_cmp(&v::dist(&self.center, b)));
+        let escaping = v::dist(&self.center, main_star) <= ROB_STAR_CLEARANCE;
+        let bearing = match target {
+            _ if escaping => v::angle_from_line(main_star, &self.center),
+            Some(target) => v::angle_from_line(&self.center, target),
+            None => return v::zero(),
+        };
+        let off = (bearing - self.view_angle + PI).rem_euclid(2.0 * PI) - PI;
+        let heading = v::from_angle(bearing, 1.0);
+        let closing = self.velocity.x * heading.x + self.velocity.y * heading.y;
+        let burn = off.abs() < ROB_THRUST_CONE && (escaping || closing < ROB_CRUISE);
+        Vector2 {
+            x: if off.abs() < ROB_ALIGNED { 0.0 } else { off.signum() },
+            y: if burn { -1.0 } else { 0.0 },
+        }
+    }
+
+    /// @brief  Runs a frame of input, movement and combat against every ship in `others`
+    pub unsafe fn update(&mut self, main_star: &Vector2, others: &mut [Ship], rules: &Rules, delta: f32) {
         self.rob_timer.update(delta);
-        self.rocket_cooldown.update(delta);
+        let enemies: Vec<Vector2> = others.iter().filter(|o| !o.dead).map(|o| o.center).collect();
+        let cooldown_rate = self.skill * if self.rapid_fire.is_running() { 2.0 } else { 1.0 };
+        self.weapons.iter_mut().for_each(|w| w.cooldown.update(delta * cooldown_rate));
+        self.shield_timer.update(delta);
+        self.invulnerable.update(delta);
+        self.rapid_fire.update(delta);
+        self.shield_boost.update(delta);
+        self.spread.update(delta);
         let switch_view_angle = match self.player {
-            Player::One => IsKeyPressed(key!(Q)),
-            Player::Two => IsKeyPressed(key!(RightShift)),
+            Player::One | Player::Two => self.controls.pressed(Action::ToggleView),
             Player::Rob => !self.rob_timer.is_running(), 
         };
-        let shoot_rocket = !self.rocket_cooldown.is_running() 
-            && match self.player {
-            Player::One => IsKeyPressed(key!(E)),
-            Player::Two => IsKeyPressed(key!(End)),
+        let (fire_pressed, fire_held) = match self.player {
+            Player::One | Player::Two => (self.controls.pressed(Action::Fire), self.controls.down(Action::Fire)),
             Player::Rob => {
-                let dist = v::dist(&self.center, &other.center);
-                self.in_sight(dist / 360.0 * PI / 6.0, &other) 
-                    || self.in_sight(-PI / 6.0 * dist / 360.0, &other) 
+                let sighted = others.iter().filter(|o| !o.dead).any(|other| {
+                    let dist = v::dist(&self.center, &other.center);
+                    self.in_sight(dist / 360.0 * PI / 6.0, other) 
+                        || self.in_sight(-PI / 6.0 * dist / 360.0, other)
+                });
+                (sighted, sighted)
             },
         };
+        let switch_weapon = match self.player {
+            Player::One | Player::Two => self.controls.pressed(Action::SwitchWeapon),
+            Player::Rob => !self.weapons[self.weapon].has_ammo(),
+        };
+        let jump = match self.player {
+            Player::One | Player::Two => self.controls.pressed(Action::Hyperspace),
+            Player::Rob => v::dist(&self.center, main_star) < 40.0,
+        };
+        let toggle_trajectory = match self.player {
+            Player::One | Player::Two => self.controls.pressed(Action::Trajectory),
+            Player::Rob => false,
+        };
         if switch_view_angle { 
             self.view_mode = !self.view_mode;
         }
-        if shoot_rocket {
-            self.rockets.push(Rocket::new(v::add(&self.center, &v::from_angle(self.view_angle, 25.0)), self.view_angle));
-            self.rocket_cooldown.start();
+        if toggle_trajectory {
+            self.show_trajectory = !self.show_trajectory;
+        }
+        if switch_weapon {
+            self.next_weapon();
+        }
+        if jump && self.hyperspace > 0 {
+            self.hyperspace -= 1;
+            self.hyperspace_jump();
         }
+        self.fire(fire_pressed, fire_held, delta);
         self.input_vector = match self.player {
-            Player::One => get_input!(W, A, S, D),
-            Player::Two => get_input!(Up, Left, Down, Right),
+            Player::One | Player::Two => self.controls.axis(),
+            Player::Rob if rules.flight_model == FlightModel::Inertial => self.rob_inertial(main_star, &enemies),
             Player::Rob => 
                 if v::is_zero(&self.input_vector) || !self.rob_timer.is_running() {
                     self.rob_input(main_star)
@@ -106,50 +288,348 @@ impl Ship {
             ,
         };
 
-        if self.input_vector.x != 0.0 {
-            self.move_angle = v::angle_from_line(&self.center, &main_star) - PI / 2.0 * self.input_vector.x;
+        // only the inertial engine burns fuel, arcade steering is free
+        if self.fuel <= 0.0 && rules.flight_model == FlightModel::Inertial {
+            self.input_vector.y = self.input_vector.y.max(0.0);
+        }
+        let thrust = match rules.flight_model {
+            FlightModel::Arcade => {
+                self.steer_arcade(main_star);
+                v::zero()
+            },
+            FlightModel::Inertial => self.steer_inertial(rules, delta),
+        };
+        if self.thrusting {
+            self.fuel = (self.fuel - delta).max(0.0);
+        }
+        self.step(main_star, &thrust, rules, delta);
+
+        self.trajectory.clear();
+        if self.show_trajectory && rules.allow_trajectory() {
+            self.trajectory = self.predict(main_star, rules);
+        }
+
+        let protected = self.invulnerable.is_running() || self.shield_boost.is_running();
+        let mut rocket_damage = 0.0;
+        let mut beam_damage = 0.0;
+        for other in others.iter_mut() {
+            while let Some(i) = other.rockets.iter().position(|r| r.collide_with(self)) {
+                let rocket = other.rockets.remove(i);
+                rocket_damage += rocket.damage(rules);
+                other.events.push(ShipEvent::RocketHit(rocket.center));
+                other.shots_hit += 1;
+            }
+            if let Some((start, end)) = other.beam {
+                if self.line_hits(start, end) {
+                    beam_damage += rules.rocket_damage * WeaponKind::Laser.damage_scale() * delta;
+                    // a burst counts as one shot fired, so it counts as one hit however long it lands
+                    if !other.beam_hit {
+                        other.beam_hit = true;
+                        other.shots_hit += 1;
+                    }
+                }
+            }
+        }
+        if CheckCollisionPointCircle(self.center, *main_star, 5.0) {
+            self.deaths_star += 1;
+            self.lose_life(main_star, &enemies, rules);
+        } else if !protected {
+            if rocket_damage > 0.0 {
+                self.take_damage(rocket_damage, main_star, &enemies, rules);
+                self.invulnerable.start();
+            } else if beam_damage > 0.0 {
+                self.take_damage(beam_damage, main_star, &enemies, rules);
+            }
+        }
+        if rules.damage_model() && !self.shield_timer.is_running() {
+            self.shield = (self.shield + rules.shield_regen * delta).min(rules.max_shield);
+        }
+
+        for i in 0..self.rockets.len() {
+            self.rockets[i].update(main_star, &enemies, rules, delta);
+        }
+        for rocket in self.rockets.iter().filter(|r| r.life <= 0.0) {
+            self.events.push(ShipEvent::RocketExpired(rocket.center));
+        }
+        self.rockets.retain(|r| r.life > 0.0);
+    }
+
+    /// @brief  Applies damage to the shield first, any left over goes to the hull
+    unsafe fn take_damage(&mut self, amount: f32, main_star: &Vector2, enemies: &[Vector2], rules: &Rules) {
+        if !rules.damage_model() {
+            self.deaths_weapon += 1;
+            self.lose_life(main_star, enemies, rules);
+            return
+        }
+        let absorbed = amount.min(self.shield);
+        self.shield -= absorbed;
+        self.health -= amount - absorbed;
+        self.shield_timer.start();
+        if self.health <= 0.0 {
+            self.deaths_weapon += 1;
+            self.lose_life(main_star, enemies, rules);
+        }
+    }
+
+    /// @brief  Loses a life to something solid, unless recently respawned or shielded
+    pub unsafe fn crash(&mut self, main_star: &Vector2, enemies: &[Vector2], rules: &Rules) {
+        if self.invulnerable.is_running() || self.shield_boost.is_running() { return }
+        self.deaths_collision += 1;
+        self.lose_life(main_star, enemies, rules);
+    }
+
+    unsafe fn lose_life(&mut self, main_star: &Vector2, enemies: &[Vector2], rules: &Rules) {
+        self.lives = self.lives.saturating_sub(1);
+        self.events.push(ShipEvent::Exploded { center: self.center, velocity: self.velocity });
+        if self.lives == 0 {
+            self.events.push(ShipEvent::Destroyed { center: self.center, velocity: self.velocity, color: self.color });
+            self.dead = true;
+            return
+        }
+        self.center = Ship::safe_point(main_star, enemies);
+        self.velocity = v::zero();
+        self.health = rules.max_health;
+        self.shield = rules.max_shield;
+        self.rockets.clear();
+        self.beam = None;
+        self.fuel = rules.max_fuel;
+        self.invulnerable.start();
+    }
+
+    /// @brief  Jumps to a random point on screen, wherever that may be
+    unsafe fn hyperspace_jump(&mut self) {
+        let mut rng = rand::thread_rng();
+        self.center = Vector2 {
+            x: rng.gen_range(0.0..ARENA_WIDTH as f32),
+            y: rng.gen_range(0.0..ARENA_HEIGHT as f32),
+        };
+        self.velocity = v::zero();
+    }
+
+    pub fn power_up(&mut self, kind: PowerUpKind, rules: &Rules) {
+        match kind {
+            PowerUpKind::RapidFire => self.rapid_fire.start(),
+            PowerUpKind::Fuel => self.fuel = rules.max_fuel,
+            PowerUpKind::Shield => self.shield_boost.start(),
+            PowerUpKind::Hyperspace => self.hyperspace = rules.hyperspace_charges.max(1),
+            PowerUpKind::Spread => self.spread.start(),
+        }
+    }
+
+    /// @brief  Moves to the next weapon that still has ammo
+    pub fn next_weapon(&mut self) {
+        for i in 1..=self.weapons.len() {
+            let next = (self.weapon + i) % self.weapons.len();
+            if self.weapons[next].has_ammo() {
+                self.weapon = next;
+                return
+            }
+        }
+    }
+
+    unsafe fn fire(&mut self, pressed: bool, held: bool, delta: f32) {
+        self.beam = None;
+        let nose = v::add(&self.center, &v::from_angle(self.view_angle, 15.0));
+        let weapon = &mut self.weapons[self.weapon];
+        if weapon.kind.continuous() {
+            if held && weapon.has_ammo() {
+                if pressed {
+                    self.shots_fired += 1;
+                    self.beam_hit = false;
+                }
+                weapon.fire(delta);
+                self.beam = Some((nose, v::add(&self.center, &v::from_angle(self.view_angle, 500.0))));
+            }
+        } else if pressed && weapon.ready() {
+            weapon.fire(1.0);
+            let angles = if self.spread.is_running() && weapon.kind != WeaponKind::Mine {
+                vec![self.view_angle - 0.2, self.view_angle, self.view_angle + 0.2]
+            } else {
+                vec![self.view_angle]
+            };
+            for angle in angles {
+                self.shots_fired += 1;
+                self.rockets.push(Rocket::new(
+                    v::add(&self.center, &v::from_angle(angle, 25.0)), angle, self.velocity, weapon.kind
+                ));
+            }
+        }
+    }
+
+    /// @brief  Picks a random spot that keeps clear of the star and the given ships
+    pub unsafe fn safe_point(main_star: &Vector2, avoid: &[Vector2]) -> Vector2 {
+        let mut rng = rand::thread_rng();
+        let width = ARENA_WIDTH as f32;
+        let height = ARENA_HEIGHT as f32;
+        let mut best = v::zero();
+        let mut best_dist = -1.0;
+        for _ in 0..32 {
+            let point = Vector2 { x: rng.gen_range(0.0..width), y: rng.gen_range(0.0..height) };
+            let dist = avoid.iter().fold(v::dist(&point, main_star), |d, a| d.min(v::dist(&point, a)));
+            if dist > best_dist {
+                best = point;
+                best_dist = dist;
+            }
+        }
+        best
+    }
+
+    /// @brief  Orbit-relative steering, left and right circle the star at a fixed speed
+    unsafe fn steer_arcade(&mut self, main_star: &Vector2) {
+        self.thrusting = false;
+        // made for keys, a stick past its deadzone counts as fully pushed so a
+        // half tilt still goes round the star instead of into it
+        let digital = |axis: f32| if axis == 0.0 { 0.0 } else { axis.signum() };
+        let (x, y) = (digital(self.input_vector.x), digital(self.input_vector.y));
+        if x != 0.0 {
+            self.move_angle = v::angle_from_line(&self.center, &main_star) - PI / 2.0 * x;
             self.velocity = v::from_angle(self.move_angle, 100.0); 
         }
-        if self.input_vector.y != 0.0 {
-            self.move_angle += self.input_vector.y * PI / 16.0;
+        if y != 0.0 {
+            self.move_angle += y * PI / 16.0;
             self.velocity = v::from_angle(self.move_angle, 100.0);
         }
-        if !self.view_mode { 
+    }
+
+    /// @brief  Newtonian steering, returns the thrust to apply this frame
+    fn steer_inertial(&mut self, rules: &Rules, delta: f32) -> Vector2 {
+        self.view_angle += self.input_vector.x * rules.turn_rate * delta;
+        self.thrusting = self.input_vector.y < 0.0;
+        if self.thrusting {
+            return v::from_angle(self.view_angle, rules.thrust)
+        }
+        v::zero()
+    }
+
+    /// @brief  Moves the ship under its current velocity, thrust, gravity and screen wrap
+    unsafe fn step(&mut self, main_star: &Vector2, thrust: &Vector2, rules: &Rules, delta: f32) {
+        let arcade = rules.flight_model == FlightModel::Arcade;
+        if arcade && !self.view_mode { 
             self.view_angle = v::to_angle(&self.velocity);
         }
-        self.velocity = v::add(&self.velocity, &v::gravitate(&self.center, &main_star));
-        self.center = v::add(&self.center, &v::scale(&self.velocity, delta));
-        self.center = v::wrap(&self.center, GetScreenWidth() as f32, GetScreenHeight() as f32);
+        let mut body = Body { position: self.center, velocity: self.velocity };
+        physics::integrate(&mut body, main_star, thrust, rules.integrator, delta);
+        self.velocity = body.velocity;
+        self.center = v::wrap(&body.position, ARENA_WIDTH as f32, ARENA_HEIGHT as f32);
 
-        if self.view_mode {
+        if !arcade {
+            self.velocity = v::scale(&self.velocity, (1.0 - rules.drag * delta).max(0.0));
+            if let Some(max_speed) = rules.max_speed {
+                if v::len(&self.velocity) > max_speed {
+                    self.velocity = v::scale(&v::norm(&self.velocity), max_speed);
+                }
+            }
+        }
+        if arcade && self.view_mode {
             self.view_angle = v::angle_from_line(&self.center, &main_star);
         }
+    }
 
-        if CheckCollisionPointCircle(self.center, *main_star, 5.0) 
-            || other.rockets.iter().any(|r| r.collide_with(self)) {
-            self.dead = true;
+    /// @brief  Steps a copy of the ship forward with no input and returns where it goes
+    /// @note   Stops early if the path falls into the star
+    pub unsafe fn predict(&self, main_star: &Vector2, rules: &Rules) -> Vec<Vector2> {
+        let delta = 1.0 / 60.0;
+        let mut points: Vec<Vector2> = vec![];
+        let mut ghost = Ship {
+            rockets: vec![],
+            trajectory: vec![],
+            ..self.clone()
+        };
+        let steps = (rules.trajectory_time / delta) as usize;
+        for _ in 0..steps {
+            ghost.step(main_star, &v::zero(), rules, delta);
+            if CheckCollisionPointCircle(ghost.center, *main_star, 5.0) { break }
+            points.push(ghost.center);
         }
+        points
+    }
 
-        for i in 0..self.rockets.len() {
-            self.rockets[i].update(main_star, delta);
+    pub unsafe fn draw_trajectory(&self, color: Color) {
+        let spacing = 3;
+        let count = self.trajectory.len();
+        for i in (0..count).step_by(spacing) {
+            let fade = 1.0 - i as f32 / count as f32;
+            DrawCircleV(self.trajectory[i], 1.5, Fade(color, fade * 0.8));
         }
-        self.rockets.retain(|r| r.life > 0.0);
     }
 
-    pub unsafe fn draw(&self) {
+    pub unsafe fn draw(&self, stroke: Stroke) {
         let point0 = v::add(&self.center, &v::from_angle(self.view_angle, 15.0));
         let point1 = v::add(&self.center, &v::from_angle(self.view_angle + 3.0 * PI / 4.0, 10.0));
         let point2 = v::add(&self.center, &v::from_angle(self.view_angle + 5.0 * PI / 4.0, 10.0));
-        let color = match self.player {
-            Player::One => GREEN,
-            Player::Two => GOLD,
-            Player::Rob => RED, 
+        let color = self.color;
+        self.draw_trajectory(color);
+        if self.thrusting {
+            let flame = v::sub(&self.center, &v::from_angle(self.view_angle, rand::thread_rng().gen_range(12.0..18.0)));
+            stroke.line(v::midpoint(&point1, &point2), flame, ORANGE);
+        }
+        self.rockets.iter().for_each(|r| r.draw(stroke));
+        if let Some((start, end)) = self.beam {
+            DrawLineEx(start, end, 2.0, Fade(color, 0.8));
+        }
+        // a destroyed hull is left to the debris
+        if self.dead {
+            return
+        }
+        if self.invulnerable.is_running() && (self.invulnerable.timer * 10.0) as i32 % 2 == 0 {
+            return
+        }
+        DrawCircleV(self.center, 2.0, WHITE);
+        stroke.line(point0, point1, color);
+        stroke.line(point1, point2, color);
+        stroke.line(point2, point0, color);
+        if self.shield_boost.is_running() {
+            stroke.circle(self.center, 20.0, SKYBLUE);
+        } else if self.shield > 0.0 && self.shield_timer.is_running() {
+            stroke.circle(self.center, 18.0, Fade(SKYBLUE, 0.6));
+        }
+    }
+
+    /// @brief  Name, lives, shield and health in the given corner of the screen
+    pub unsafe fn draw_hud(&self, rules: &Rules, pos: TextPosition) {
+        let width = 100.0;
+        let padding = 8.0;
+        let x = match pos {
+            TextPosition::TL | TextPosition::BL => padding,
+            TextPosition::TR | TextPosition::BR => ARENA_WIDTH as f32 - padding - width,
+        };
+        let y = match pos {
+            TextPosition::TL | TextPosition::TR => padding + 24.0,
+            TextPosition::BL | TextPosition::BR => ARENA_HEIGHT as f32 - padding - 44.0 - 22.0,
+        };
+        let name_x = match pos {
+            TextPosition::TL | TextPosition::BL => x as i32,
+            TextPosition::TR | TextPosition::BR => ARENA_WIDTH - padding as i32 - MeasureText(rl_str!(self.name), 20),
         };
-        DrawCircleV(self.center, 2.0, color);
-        DrawLineV(point0, point1, WHITE);
-        DrawLineV(point1, point2, WHITE);
-        DrawLineV(point2, point0, WHITE);
-        self.rockets.iter().for_each(|r| r.draw());
+        DrawText(rl_str!(self.name), name_x, y as i32 - 22, 20, self.color);
+        if rules.damage_model() {
+            draw_bar(Rectangle { x, y, width, height: 6.0 }, self.shield / rules.max_shield, SKYBLUE, BLACK);
+            draw_bar(Rectangle { x, y: y + 8.0, width, height: 6.0 }, self.health / rules.max_health, GREEN, BLACK);
+        }
+        if rules.flight_model == FlightModel::Inertial && rules.max_fuel > 0.0 {
+            draw_bar(Rectangle { x, y: y + 16.0, width, height: 6.0 }, self.fuel / rules.max_fuel, YELLOW, BLACK);
+        }
+        let mut status: Vec<String> = vec![];
+        if rules.lives() > 1 {
+            status.push(format!("Lives: {}", self.lives));
+        }
+        if rules.hyperspace_charges > 0 {
+            status.push(format!("Jumps: {}", self.hyperspace));
+        }
+        if !status.is_empty() {
+            let y_off = match pos {
+                TextPosition::TL | TextPosition::TR => 0,
+                TextPosition::BL | TextPosition::BR => 1,
+            };
+            draw_text(status.join("  "), pos, y_off, 20, WHITE);
+        }
+        if self.weapons.len() > 1 {
+            let y_off = match pos {
+                TextPosition::TL | TextPosition::TR => 1,
+                TextPosition::BL | TextPosition::BR => 2,
+            };
+            draw_text(self.weapons[self.weapon].label(), pos, y_off, 20, GRAY);
+        }
     }
 
     pub unsafe fn draw_sight(&self, angle_off: f32) {
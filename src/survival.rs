@@ -0,0 +1,109 @@
+use raylib_ffi::*;
+use colors::*;
+use crate::rl::gui::{draw_label, draw_text, fmt_time, TextPosition};
+use crate::rl::timer::Timer;
+use crate::rules::Rules;
+use crate::ship::{Ship, ShipEvent};
+use crate::rl::view::{ARENA_WIDTH, ARENA_HEIGHT};
+use std::slice;
+
+/// Waves of Robs that grow in number and skill until the player runs out of lives
+pub struct Survival {
+    pub wave: u32,
+    pub bots: Vec<Ship>,
+    pub kills: u32,
+    pub time: f32,
+    /// events from bots destroyed this frame, they are gone before anyone else sees them
+    pub wrecks: Vec<ShipEvent>,
+    intermission: Timer,
+    between_waves: bool,
+    death_time: f32,
+}
+
+impl Survival {
+    pub fn new() -> Survival {
+        let mut intermission = Timer::new(3.0);
+        intermission.start();
+        Survival {
+            wave: 0,
+            bots: vec![],
+            kills: 0,
+            time: 0.0,
+            wrecks: vec![],
+            intermission,
+            between_waves: true,
+            death_time: 0.0,
+        }
+    }
+
+    pub fn score(&self) -> u32 {
+        self.kills * 100 + self.time as u32 * 5 + self.wave.saturating_sub(1) * 250
+    }
+
+    unsafe fn spawn_wave(&mut self, main_star: &Vector2, rules: &Rules, player: &Ship) {
+        self.wave += 1;
+        let count = self.wave.min(6);
+        let skill = 1.0 + 0.15 * (self.wave - 1) as f32;
+        for _ in 0..count {
+            let mut avoid = vec![player.center];
+            avoid.extend(self.bots.iter().map(|b| b.center));
+            let center = Ship::safe_point(main_star, &avoid);
+            self.bots.push(Ship::new_bot(rules, center, skill));
+        }
+    }
+
+    /// @brief  Runs the bots against the player and moves between waves
+    pub unsafe fn update(&mut self, main_star: &Vector2, player: &mut Ship, rules: &Rules, delta: f32) {
+        if player.dead {
+            self.death_time += delta;
+            return
+        }
+        self.time += delta;
+        for bot in self.bots.iter_mut() {
+            bot.update(main_star, slice::from_mut(player), rules, delta);
+        }
+        let before = self.bots.len();
+        for bot in self.bots.iter_mut().filter(|b| b.dead) {
+            self.wrecks.append(&mut bot.events);
+        }
+        self.bots.retain(|b| !b.dead);
+        self.kills += (before - self.bots.len()) as u32;
+
+        if self.bots.is_empty() && !self.between_waves {
+            player.replenish(rules);
+            self.between_waves = true;
+            self.intermission.start();
+        }
+        if self.between_waves {
+            self.intermission.update(delta);
+            if !self.intermission.is_running() {
+                self.between_waves = false;
+                self.spawn_wave(main_star, rules, player);
+            }
+        }
+    }
+
+    /// @brief  True once the player has died and the wreck has been on screen for a moment
+    pub fn finished(&self) -> bool {
+        self.death_time > 2.0
+    }
+
+    pub unsafe fn draw(&self) {
+        draw_text(format!("Wave {}", self.wave.max(1)), TextPosition::TR, 0, 20, WHITE);
+        draw_text(format!("Score {}", self.score()), TextPosition::TR, 1, 20, WHITE);
+        draw_text(fmt_time(self.time), TextPosition::TR, 2, 20, GRAY);
+        if self.between_waves {
+            draw_label(&format!("Wave {}", self.wave + 1), 40, ARENA_WIDTH / 2, ARENA_HEIGHT / 2 - 60, WHITE);
+        }
+    }
+
+    pub unsafe fn draw_game_over(&self) {
+        let x = ARENA_WIDTH / 2;
+        let y = ARENA_HEIGHT / 2;
+        draw_label("Game Over.", 60, x, y - 80, RED);
+        draw_label(&format!("Score: {}", self.score()), 30, x, y - 10, WHITE);
+        draw_label(&format!("Wave {}  -  {} kills", self.wave, self.kills), 20, x, y + 30, GRAY);
+        draw_label(&format!("Survived {}", fmt_time(self.time)), 20, x, y + 55, GRAY);
+        draw_label("Press Enter", 20, x, y + 110, WHITE);
+    }
+}
@@ -0,0 +1,459 @@
+use raylib_ffi::*;
+use colors::*;
+use crate::key;
+use crate::profile::Profiles;
+use crate::rl::gui::{draw_label, draw_text, TextPosition, Theme};
+use crate::rl::input::{menu_pressed, MenuKey};
+use crate::rl::view::{ARENA_WIDTH, ARENA_HEIGHT};
+use std::cmp::Reverse;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Format {
+    SingleElimination,
+    DoubleElimination,
+    RoundRobin,
+}
+
+impl Format {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Format::SingleElimination => "Single Elimination",
+            Format::DoubleElimination => "Double Elimination",
+            Format::RoundRobin => "Round Robin",
+        }
+    }
+
+    pub fn next(&self) -> Format {
+        match self {
+            Format::SingleElimination => Format::DoubleElimination,
+            Format::DoubleElimination => Format::RoundRobin,
+            Format::RoundRobin => Format::SingleElimination,
+        }
+    }
+
+    /// @brief  Match losses before an entrant is knocked out, none for round robin
+    fn max_losses(&self) -> Option<u32> {
+        match self {
+            Format::SingleElimination => Some(1),
+            Format::DoubleElimination => Some(2),
+            Format::RoundRobin => None,
+        }
+    }
+}
+
+/// A best-of-N series between two entrants, `b` is empty for a bye
+pub struct Match {
+    pub a: usize,
+    pub b: Option<usize>,
+    pub wins: [u32; 2],
+    pub round: u32,
+    pub losers: bool,
+}
+
+impl Match {
+    /// @brief  The winning entrant once either side has won more than half the games
+    pub fn winner(&self, best_of: u32) -> Option<usize> {
+        let b = match self.b {
+            Some(b) => b,
+            None => return Some(self.a),
+        };
+        let needed = best_of / 2 + 1;
+        if self.wins[0] >= needed {
+            Some(self.a)
+        } else if self.wins[1] >= needed {
+            Some(b)
+        } else {
+            None
+        }
+    }
+}
+
+/// A bracket of hot-seat matches between local profiles
+pub struct Tournament {
+    pub format: Format,
+    pub best_of: u32,
+    pub entrants: Vec<String>,
+    pub matches: Vec<Match>,
+    losses: Vec<u32>,
+    /// rounds each entrant sat out, the next bye goes to whoever has had fewest
+    byes: Vec<u32>,
+    round: u32,
+}
+
+impl Tournament {
+    pub fn new(format: Format, best_of: u32, entrants: Vec<String>) -> Tournament {
+        let mut tournament = Tournament {
+            format,
+            best_of,
+            losses: vec![0; entrants.len()],
+            byes: vec![0; entrants.len()],
+            entrants,
+            matches: vec![],
+            round: 0,
+        };
+        match format {
+            Format::RoundRobin => tournament.schedule_round_robin(),
+            _ => tournament.schedule_round(),
+        }
+        tournament
+    }
+
+    /// @brief  Every pairing up front, rotating all but the first entrant each round
+    fn schedule_round_robin(&mut self) {
+        let mut seats: Vec<Option<usize>> = (0..self.entrants.len()).map(Some).collect();
+        if seats.len() % 2 == 1 {
+            seats.push(None);
+        }
+        let n = seats.len();
+        for round in 1..n as u32 {
+            for i in 0..n / 2 {
+                if let (Some(a), Some(b)) = (seats[i], seats[n - 1 - i]) {
+                    self.matches.push(Match { a, b: Some(b), wins: [0; 2], round, losers: false });
+                }
+            }
+            seats[1..].rotate_right(1);
+        }
+        self.round = n as u32 - 1;
+    }
+
+    /// @brief  Takes out the entrant who has sat out fewest rounds, the latest on a tie
+    fn take_odd_one(&self, pool: &mut Vec<usize>) -> usize {
+        let i = (0..pool.len()).rev().min_by_key(|i| self.byes[pool[*i]]).unwrap_or(0);
+        pool.remove(i)
+    }
+
+    /// @brief  Pairs up everyone still in, entrants with the same number of losses
+    ///         meet first and whoever is left over plays across brackets or gets a bye
+    fn schedule_round(&mut self) {
+        let max_losses = self.format.max_losses().unwrap_or(1);
+        let alive: Vec<usize> = (0..self.entrants.len()).filter(|i| self.losses[*i] < max_losses).collect();
+        if alive.len() < 2 {
+            return
+        }
+        self.round += 1;
+        let mut leftover: Vec<usize> = vec![];
+        for losses in 0..max_losses {
+            let mut pool: Vec<usize> = alive.iter().copied().filter(|i| self.losses[*i] == losses).collect();
+            if pool.len() % 2 == 1 {
+                leftover.push(self.take_odd_one(&mut pool));
+            }
+            for pair in pool.chunks(2) {
+                self.matches.push(Match { a: pair[0], b: Some(pair[1]), wins: [0; 2], round: self.round, losers: losses > 0 });
+            }
+        }
+        let bye = match leftover.len() % 2 {
+            1 => Some(self.take_odd_one(&mut leftover)),
+            _ => None,
+        };
+        for pair in leftover.chunks(2) {
+            let losers = pair.iter().all(|i| self.losses[*i] > 0);
+            self.matches.push(Match { a: pair[0], b: Some(pair[1]), wins: [0; 2], round: self.round, losers });
+        }
+        if let Some(a) = bye {
+            self.byes[a] += 1;
+            self.matches.push(Match { a, b: None, wins: [0; 2], round: self.round, losers: self.losses[a] > 0 });
+        }
+    }
+
+    fn current_index(&self) -> Option<usize> {
+        self.matches.iter().position(|m| m.winner(self.best_of).is_none())
+    }
+
+    /// @brief  Names of the two entrants in the next undecided match
+    pub fn next_match(&self) -> Option<(&str, &str)> {
+        let m = &self.matches[self.current_index()?];
+        Some((&self.entrants[m.a], &self.entrants[m.b?]))
+    }
+
+    /// @brief  Records a game of the current match, returns the winner and loser
+    ///         names once the match is decided so their ratings can be updated
+    pub fn report(&mut self, first_won: bool) -> Option<(String, String)> {
+        let i = self.current_index()?;
+        let best_of = self.best_of;
+        let m = &mut self.matches[i];
+        m.wins[if first_won { 0 } else { 1 }] += 1;
+        let winner = m.winner(best_of)?;
+        let loser = if winner == m.a { m.b? } else { m.a };
+        self.losses[loser] += 1;
+        let result = (self.entrants[winner].clone(), self.entrants[loser].clone());
+        if self.format != Format::RoundRobin && self.current_index().is_none() {
+            self.schedule_round();
+        }
+        Some(result)
+    }
+
+    fn match_wins(&self, entrant: usize) -> u32 {
+        self.matches.iter()
+            .filter(|m| m.b.is_some() && m.winner(self.best_of) == Some(entrant))
+            .count() as u32
+    }
+
+    /// @brief  Entrants ordered by matches won, elimination brackets put
+    ///         whoever is still standing first
+    pub fn standings(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.entrants.len()).collect();
+        match self.format {
+            Format::RoundRobin => order.sort_by_key(|i| (Reverse(self.match_wins(*i)), self.losses[*i])),
+            _ => order.sort_by_key(|i| (self.losses[*i], Reverse(self.match_wins(*i)))),
+        }
+        order
+    }
+
+    pub fn champion(&self) -> Option<&str> {
+        if self.current_index().is_some() {
+            return None
+        }
+        self.standings().first().map(|i| self.entrants[*i].as_str())
+    }
+
+    /// @brief  The series score shown over a tournament game
+    pub unsafe fn draw_score(&self) {
+        let i = match self.current_index() {
+            Some(i) => i,
+            None => return,
+        };
+        let m = &self.matches[i];
+        let text = format!("{} - {}  (best of {})", m.wins[0], m.wins[1], self.best_of);
+        draw_label(&text, 20, ARENA_WIDTH / 2, 18, GRAY);
+    }
+
+    pub unsafe fn draw(&self, profiles: &Profiles) {
+        let x = ARENA_WIDTH / 2;
+        draw_label(self.format.name(), 30, x, 30, WHITE);
+        let mut lines: Vec<(String, Color)> = vec![];
+        if self.format == Format::RoundRobin {
+            for i in self.standings() {
+                lines.push((format!("{}  {} wins  ({:.0})", self.entrants[i], self.match_wins(i), profiles.elo(&self.entrants[i])), WHITE));
+            }
+            lines.push((String::new(), WHITE));
+        }
+        let current = self.current_index();
+        for (i, m) in self.matches.iter().enumerate() {
+            let bracket = if m.losers { "L" } else { "R" };
+            let text = match m.b {
+                Some(b) => format!(
+                    "{}{}  {} {} - {} {}",
+                    bracket, m.round, self.entrants[m.a], m.wins[0], m.wins[1], self.entrants[b]
+                ),
+                None => format!("{}{}  {} (bye)", bracket, m.round, self.entrants[m.a]),
+            };
+            let color = if current == Some(i) {
+                YELLOW
+            } else if m.winner(self.best_of).is_some() {
+                WHITE
+            } else {
+                GRAY
+            };
+            lines.push((text, color));
+        }
+        // keep the current match in view once the list outgrows the screen
+        let fit = ((ARENA_HEIGHT - 140) / 24) as usize;
+        let focus = current.map_or(lines.len(), |i| i + lines.len() - self.matches.len() + 1);
+        let start = focus.saturating_sub(fit).min(lines.len().saturating_sub(fit));
+        for (row, (text, color)) in lines.iter().skip(start).take(fit).enumerate() {
+            draw_label(text, 20, x, 70 + row as i32 * 24, *color);
+        }
+        match (self.champion(), self.next_match()) {
+            (Some(champion), _) => draw_label(&format!("{} wins the tournament!", champion), 30, x, ARENA_HEIGHT - 50, GOLD),
+            (None, Some((a, b))) => draw_text(format!("Next: {} vs {}  -  Enter", a, b), TextPosition::BL, 1, 20, WHITE),
+            _ => {},
+        }
+    }
+}
+
+pub enum SetupEvent {
+    Back,
+    Start(Tournament),
+}
+
+/// Picks the entrants, format and series length before a tournament
+pub struct TournamentSetup {
+    cursor: usize,
+    chosen: Vec<bool>,
+    format: Format,
+    best_of: u32,
+    theme: Theme,
+}
+
+impl TournamentSetup {
+    pub fn new(profiles: &Profiles, theme: Theme) -> TournamentSetup {
+        TournamentSetup {
+            cursor: 0,
+            chosen: vec![false; profiles.list.len()],
+            format: Format::SingleElimination,
+            best_of: 3,
+            theme,
+        }
+    }
+
+    /// @brief  Rows after the profiles, format and series length then the start button
+    const EXTRA_ROWS: usize = 3;
+
+    pub unsafe fn update(&mut self, profiles: &Profiles) -> Option<SetupEvent> {
+        if menu_pressed(MenuKey::Back) {
+            return Some(SetupEvent::Back)
+        }
+        self.chosen.resize(profiles.list.len(), false);
+        let count = profiles.list.len();
+        let rows = count + TournamentSetup::EXTRA_ROWS;
+        if menu_pressed(MenuKey::Up) {
+            self.cursor = (self.cursor + rows - 1) % rows;
+        }
+        if menu_pressed(MenuKey::Down) {
+            self.cursor = (self.cursor + 1) % rows;
+        }
+        let accept = menu_pressed(MenuKey::Accept);
+        let step = menu_pressed(MenuKey::Left) || menu_pressed(MenuKey::Right) || accept;
+        if accept && self.cursor < count {
+            self.chosen[self.cursor] = !self.chosen[self.cursor];
+        }
+        if IsKeyPressed(key!(F)) || (step && self.cursor == count) {
+            self.format = self.format.next();
+        }
+        if IsKeyPressed(key!(B)) || (step && self.cursor == count + 1) {
+            self.best_of = if self.best_of >= 7 { 1 } else { self.best_of + 2 };
+        }
+        if accept && self.cursor == count + 2 {
+            let entrants: Vec<String> = profiles.list.iter().zip(self.chosen.iter())
+                .filter(|(_, chosen)| **chosen)
+                .map(|(p, _)| p.name.clone())
+                .collect();
+            if entrants.len() >= 2 {
+                return Some(SetupEvent::Start(Tournament::new(self.format, self.best_of, entrants)))
+            }
+        }
+        None
+    }
+
+    pub unsafe fn draw(&self, profiles: &Profiles) {
+        let (x, t) = (ARENA_WIDTH / 2, &self.theme);
+        draw_label("Tournament", 40, x, 50, t.color0);
+        let count = profiles.list.len();
+        let entered = self.chosen.iter().filter(|c| **c).count();
+        let mut rows: Vec<(String, Color)> = profiles.list.iter().enumerate().map(|(i, profile)| {
+            let mark = if self.chosen.get(i).copied().unwrap_or(false) { "[x]" } else { "[ ]" };
+            (format!("{} {} ({:.0})", mark, profile.name, profile.elo), profile.color)
+        }).collect();
+        rows.push((format!("< {} >", self.format.name()), t.color0));
+        rows.push((format!("< Best of {} >", self.best_of), t.color0));
+        rows.push(("Start".to_string(), if entered >= 2 { t.color0 } else { t.muted }));
+        for (i, (text, color)) in rows.into_iter().enumerate() {
+            // a gap between the entrants and the options under them
+            let y = 100 + i as i32 * 28 + if i >= count { 14 } else { 0 };
+            let text = if i == self.cursor { format!("> {} <", text) } else { text };
+            draw_label(&text, 20, x, y, color);
+        }
+        draw_text("Enter / South choose  F format  B best of".to_string(), TextPosition::BL, 1, 20, t.muted);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entrants(n: usize) -> Vec<String> {
+        (0..n).map(|i| format!("P{}", i)).collect()
+    }
+
+    /// @brief  Plays every game out with `first_won` deciding each, guarding against a bracket that never ends
+    fn play(format: Format, n: usize, best_of: u32, first_won: impl Fn(usize) -> bool) -> Tournament {
+        let mut t = Tournament::new(format, best_of, entrants(n));
+        let mut games = 0;
+        while t.next_match().is_some() {
+            t.report(first_won(games));
+            games += 1;
+            assert!(games < 1000, "bracket never finished");
+        }
+        t
+    }
+
+    fn played(t: &Tournament) -> usize {
+        t.matches.iter().filter(|m| m.b.is_some()).count()
+    }
+
+    #[test]
+    fn single_elimination_needs_one_loss_from_everyone_else() {
+        for n in [2, 3, 5, 8] {
+            let t = play(Format::SingleElimination, n, 1, |_| true);
+            assert_eq!(played(&t), n - 1, "{} entrants", n);
+            assert!(t.champion().is_some());
+            let champion = t.standings()[0];
+            assert_eq!(t.losses[champion], 0);
+            assert!((0..n).filter(|i| *i != champion).all(|i| t.losses[i] == 1));
+        }
+    }
+
+    #[test]
+    fn double_elimination_knocks_out_on_the_second_loss() {
+        for n in [3, 5, 8] {
+            let patterns: [fn(usize) -> bool; 3] = [|_| true, |_| false, |g| g % 3 != 0];
+            for pattern in patterns {
+                let t = play(Format::DoubleElimination, n, 1, pattern);
+                let champion = t.standings()[0];
+                assert!(t.losses[champion] < 2, "{} entrants", n);
+                assert!((0..n).filter(|i| *i != champion).all(|i| t.losses[i] == 2), "{} entrants", n);
+            }
+        }
+    }
+
+    #[test]
+    fn double_elimination_pools_meet_within_their_bracket() {
+        let t = play(Format::DoubleElimination, 8, 1, |_| true);
+        // round two of an even bracket has a full winners' and losers' pool
+        let round_two: Vec<&Match> = t.matches.iter().filter(|m| m.round == 2).collect();
+        assert_eq!(round_two.iter().filter(|m| m.losers).count(), 2);
+        assert_eq!(round_two.iter().filter(|m| !m.losers).count(), 2);
+    }
+
+    #[test]
+    fn byes_rotate() {
+        for format in [Format::SingleElimination, Format::DoubleElimination] {
+            for n in [3, 5, 8] {
+                let t = play(format, n, 1, |_| true);
+                let (most, least) = (t.byes.iter().max().unwrap(), t.byes.iter().min().unwrap());
+                assert!(most - least <= 1, "{} entrants, byes {:?}", n, t.byes);
+                assert_eq!(t.byes.iter().sum::<u32>() as usize, t.matches.len() - played(&t));
+            }
+        }
+    }
+
+    #[test]
+    fn five_entrants_never_give_the_same_player_two_byes() {
+        let t = play(Format::SingleElimination, 5, 1, |_| true);
+        assert!(t.byes.iter().all(|b| *b <= 1));
+    }
+
+    #[test]
+    fn round_robin_pairs_everyone_once() {
+        for n in [3, 5, 8] {
+            let t = play(Format::RoundRobin, n, 3, |g| g % 2 == 0);
+            assert_eq!(played(&t), n * (n - 1) / 2);
+            for a in 0..n {
+                for b in a + 1..n {
+                    let meetings = t.matches.iter()
+                        .filter(|m| (m.a == a && m.b == Some(b)) || (m.a == b && m.b == Some(a)))
+                        .count();
+                    assert_eq!(meetings, 1, "{} v {} of {}", a, b, n);
+                }
+            }
+            assert!(t.champion().is_some());
+        }
+    }
+
+    #[test]
+    fn round_robin_standings_follow_match_wins() {
+        // the first named side always wins, so the earlier seat wins every meeting it has
+        let t = play(Format::RoundRobin, 4, 1, |_| true);
+        let wins: Vec<u32> = t.standings().iter().map(|i| t.match_wins(*i)).collect();
+        assert!(wins.windows(2).all(|w| w[0] >= w[1]));
+    }
+
+    #[test]
+    fn series_needs_a_majority() {
+        let mut t = Tournament::new(Format::SingleElimination, 3, entrants(2));
+        assert_eq!(t.report(true), None);
+        assert_eq!(t.report(false), None);
+        assert_eq!(t.report(true), Some(("P0".to_string(), "P1".to_string())));
+        assert_eq!(t.champion(), Some("P0"));
+    }
+}
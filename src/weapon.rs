@@ -0,0 +1,107 @@
+use crate::rl::timer::Timer;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum WeaponKind {
+    /// The original, accelerates along its heading for a second
+    Torpedo,
+    /// Leaves the ship fast and coasts, no engine
+    Shell,
+    /// Slow, but steers toward the nearest enemy
+    Homing,
+    /// Drifts under gravity and goes off when a ship gets close
+    Mine,
+    /// Continuous beam for as long as fire is held
+    Laser,
+}
+
+impl WeaponKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            WeaponKind::Torpedo => "Torpedo",
+            WeaponKind::Shell => "Shell",
+            WeaponKind::Homing => "Homing",
+            WeaponKind::Mine => "Mine",
+            WeaponKind::Laser => "Laser",
+        }
+    }
+
+    pub fn cooldown(&self) -> f32 {
+        match self {
+            WeaponKind::Torpedo => 0.5,
+            WeaponKind::Shell => 0.2,
+            WeaponKind::Homing => 1.5,
+            WeaponKind::Mine => 1.0,
+            WeaponKind::Laser => 0.0,
+        }
+    }
+
+    /// @brief  Shots per round, `None` is unlimited
+    /// @note   The laser counts seconds of beam rather than shots
+    pub fn ammo(&self) -> Option<f32> {
+        match self {
+            WeaponKind::Torpedo => None,
+            WeaponKind::Shell => None,
+            WeaponKind::Homing => Some(6.0),
+            WeaponKind::Mine => Some(4.0),
+            WeaponKind::Laser => Some(3.0),
+        }
+    }
+
+    /// @brief  Multiplier on the rules' rocket damage, per second for the laser
+    pub fn damage_scale(&self) -> f32 {
+        match self {
+            WeaponKind::Torpedo => 1.0,
+            WeaponKind::Shell => 0.6,
+            WeaponKind::Homing => 0.75,
+            WeaponKind::Mine => 1.5,
+            WeaponKind::Laser => 1.5,
+        }
+    }
+
+    pub fn continuous(&self) -> bool {
+        *self == WeaponKind::Laser
+    }
+}
+
+#[derive(Clone)]
+pub struct Weapon {
+    pub kind: WeaponKind,
+    pub cooldown: Timer,
+    pub ammo: Option<f32>,
+}
+
+impl Weapon {
+    pub fn new(kind: WeaponKind) -> Weapon {
+        Weapon {
+            kind,
+            cooldown: Timer::new(kind.cooldown()),
+            ammo: kind.ammo(),
+        }
+    }
+
+    pub fn has_ammo(&self) -> bool {
+        match self.ammo {
+            Some(ammo) => ammo > 0.0,
+            None => true,
+        }
+    }
+
+    pub fn ready(&self) -> bool {
+        self.has_ammo() && !self.cooldown.is_running()
+    }
+
+    /// @brief  Spends ammo and starts the cooldown
+    pub fn fire(&mut self, amount: f32) {
+        if let Some(ammo) = self.ammo.as_mut() {
+            *ammo = (*ammo - amount).max(0.0);
+        }
+        self.cooldown.start();
+    }
+
+    pub fn label(&self) -> String {
+        match self.ammo {
+            Some(ammo) => format!("{} {}", self.kind.name(), ammo.ceil() as i32),
+            None => self.kind.name().to_string(),
+        }
+    }
+}